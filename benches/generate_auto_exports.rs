@@ -0,0 +1,57 @@
+use crate_manager::ItemImportExportUpdater;
+use std::time::{ Duration, Instant };
+
+
+
+const CRATE_DIR:&str = "target/crate_manager/bench_crate";
+const MODULE_COUNT:usize = 10;
+const FILES_PER_MODULE:usize = 10;
+const LINES_PER_FUNCTION:usize = 489;
+const RUN_COUNT:usize = 10;
+
+
+
+/// Generate auto-exports for a synthetic crate of at least 50k lines and report the timings. Run with `cargo bench`.
+fn main() {
+	let lib_file:String = create_bench_crate();
+	let line_count:usize = count_lines(CRATE_DIR);
+
+	// Time cold runs, without parse cache.
+	let mut durations:Vec<Duration> = Vec::new();
+	for _ in 0..RUN_COUNT {
+		std::fs::write(&lib_file, "// auto-exports\n").unwrap();
+		let start:Instant = Instant::now();
+		ItemImportExportUpdater::new(&lib_file).with_threads(1).generate().unwrap();
+		durations.push(start.elapsed());
+	}
+	durations.sort();
+
+	println!("generate_auto_exports ({line_count} lines, {RUN_COUNT} runs): min {:?}, median {:?}, max {:?}", durations[0], durations[RUN_COUNT / 2], durations[RUN_COUNT - 1]);
+	std::fs::remove_dir_all(CRATE_DIR).unwrap();
+}
+
+/// Create the synthetic crate, removing any previous contents. Returns the path to its lib file.
+fn create_bench_crate() -> String {
+	let _ = std::fs::remove_dir_all(CRATE_DIR);
+	for module_index in 0..MODULE_COUNT {
+		let module_dir:String = format!("{CRATE_DIR}/module_{module_index}");
+		std::fs::create_dir_all(&module_dir).unwrap();
+		std::fs::write(format!("{module_dir}/mod.rs"), "// auto-exports\n").unwrap();
+		for file_index in 0..FILES_PER_MODULE {
+			let function_body:String = (0..LINES_PER_FUNCTION).map(|line_index| format!("\tlet value_{line_index}:&str = \"{{ {line_index} }}\"; // use {line_index} {{\n")).collect();
+			let contents:String = format!(
+				"use std::fmt::Display;\n\npub struct Item{module_index}x{file_index} {{\n\tvalue:u32\n}}\n\npub(crate) fn function_{module_index}_{file_index}() {{\n{function_body}}}\n\n/* pub fn hidden() {{}} */\npub const CONSTANT_{module_index}_{file_index}:&str = \"mod fake;\";\n"
+			);
+			std::fs::write(format!("{module_dir}/file_{file_index}.rs"), contents).unwrap();
+		}
+	}
+	format!("{CRATE_DIR}/lib.rs")
+}
+
+/// Count the lines of all files in a directory recursively.
+fn count_lines(dir:&str) -> usize {
+	std::fs::read_dir(dir).unwrap().flatten().map(|entry| {
+		let path = entry.path();
+		if path.is_dir() { count_lines(&path.to_string_lossy()) } else { std::fs::read_to_string(&path).unwrap().lines().count() }
+	}).sum()
+}
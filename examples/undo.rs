@@ -0,0 +1,16 @@
+use crate_manager::{ ItemImportExportUpdater, DEFAULT_UNDO_JOURNAL_DIR };
+
+
+
+/// Undo the last generation of a crate that was generated with the default undo journal, restoring all files it touched. Run with `cargo run --example undo [root file]`, the root file defaults to `src/lib.rs`.
+fn main() {
+	let root_file:String = std::env::args().nth(1).unwrap_or_else(|| "src/lib.rs".to_string());
+	let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&root_file).with_undo_journal(DEFAULT_UNDO_JOURNAL_DIR);
+	match updater.undo() {
+		Ok(restored_files) => restored_files.iter().for_each(|restored_file| println!("restored {restored_file}")),
+		Err(error) => {
+			eprintln!("{error}");
+			std::process::exit(1);
+		}
+	}
+}
\ No newline at end of file
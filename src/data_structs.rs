@@ -1,3 +1,7 @@
+use crate::Span;
+
+
+
 #[derive(PartialEq, Clone)]
 pub(crate) enum PubType { Pub, Super, Crate }
 impl PubType {
@@ -24,7 +28,8 @@ impl PubType {
 pub(crate) struct Import {
 	pub(crate) pub_type:Option<PubType>,
 	pub(crate) struct_type:String,
-	pub(crate) identifier:String
+	pub(crate) identifier:String,
+	pub(crate) span:Span
 }
 
 #[derive(Clone)]
@@ -32,5 +37,13 @@ pub(crate) struct Import {
 pub(crate) struct Export {
 	pub(crate) pub_type:Option<PubType>,
 	pub(crate) struct_type:String,
-	pub(crate) identifier:String
+	pub(crate) identifier:String,
+	pub(crate) span:Span
+}
+
+#[derive(Clone)]
+pub(crate) struct ParsedFile {
+	pub(crate) imports:Vec<Import>,
+	pub(crate) exports:Vec<Export>,
+	pub(crate) auto_exports_trigger:Option<usize>
 }
\ No newline at end of file
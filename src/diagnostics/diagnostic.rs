@@ -0,0 +1,85 @@
+use crate::Span;
+
+
+
+pub const DIAGNOSTIC_EXPORT_COLLISION:&str = "CM0001";
+pub const DIAGNOSTIC_UNRESOLVED_MODULE:&str = "CM0002";
+pub const DIAGNOSTIC_MISSING_LIBRARY:&str = "CM0003";
+pub const DIAGNOSTIC_UNUSED_LIBRARY:&str = "CM0004";
+pub const DIAGNOSTIC_INVALID_MANIFEST:&str = "CM0005";
+pub const DIAGNOSTIC_UNREADABLE_GIT_CONFIG:&str = "CM0006";
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity { Error, Warning, Note }
+impl Severity {
+	pub fn to_str(&self) -> &str {
+		match self {
+			Severity::Error => "error",
+			Severity::Warning => "warning",
+			Severity::Note => "note"
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DiagnosticFormat { Human, Json }
+
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+	pub severity:Severity,
+	pub code:String,
+	pub message:String,
+	pub file:String,
+	pub span:Option<Span>,
+	pub label:Option<String>,
+	pub notes:Vec<String>
+}
+impl Diagnostic {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new diagnostic.
+	pub fn new(severity:Severity, code:&str, message:&str, file:&str) -> Diagnostic {
+		Diagnostic {
+			severity,
+			code: code.to_string(),
+			message: message.to_string(),
+			file: file.to_string(),
+			span: None,
+			label: None,
+			notes: Vec::new()
+		}
+	}
+
+	/// Create a new error diagnostic.
+	pub fn error(code:&str, message:&str, file:&str) -> Diagnostic {
+		Diagnostic::new(Severity::Error, code, message, file)
+	}
+
+	/// Create a new warning diagnostic.
+	pub fn warning(code:&str, message:&str, file:&str) -> Diagnostic {
+		Diagnostic::new(Severity::Warning, code, message, file)
+	}
+
+	/// Return self with the location the diagnostic points to.
+	pub fn with_span(mut self, span:Span) -> Self {
+		self.span = Some(span);
+		self
+	}
+
+	/// Return self with a label displayed next to the carets.
+	pub fn with_label(mut self, label:&str) -> Self {
+		self.label = Some(label.to_string());
+		self
+	}
+
+	/// Return self with an additional note.
+	pub fn with_note(mut self, note:&str) -> Self {
+		self.notes.push(note.to_string());
+		self
+	}
+}
\ No newline at end of file
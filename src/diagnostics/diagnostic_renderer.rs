@@ -0,0 +1,118 @@
+use crate::{ Diagnostic, DiagnosticFormat, Span };
+use file_ref::FileRef;
+
+
+
+const TAB_WIDTH:usize = 4;
+
+
+
+/// Render a list of diagnostics in the given format. Human-readable diagnostics are separated by an empty line, JSON diagnostics are placed one per line.
+pub fn render_diagnostics(diagnostics:&[&Diagnostic], format:DiagnosticFormat) -> String {
+	match format {
+		DiagnosticFormat::Human => diagnostics.iter().map(|diagnostic| diagnostic.render()).collect::<Vec<String>>().join("\n\n"),
+		DiagnosticFormat::Json => diagnostics.iter().map(|diagnostic| diagnostic.to_json_line()).collect::<Vec<String>>().join("\n")
+	}
+}
+
+
+
+impl Diagnostic {
+
+	/* RENDERING METHODS */
+
+	/// Render the diagnostic in the given format.
+	pub fn format(&self, format:DiagnosticFormat) -> String {
+		match format {
+			DiagnosticFormat::Human => self.render(),
+			DiagnosticFormat::Json => self.to_json_line()
+		}
+	}
+
+	/// Render the diagnostic like a compiler diagnostic. Reads the source snippet from the diagnostic's file.
+	pub fn render(&self) -> String {
+		let source:Option<String> = if self.span.is_some() { FileRef::new(&self.file).read().ok() } else { None };
+		self.render_with_source(source.as_deref())
+	}
+
+	/// Render the diagnostic like a compiler diagnostic, using the given source for the snippet.
+	pub fn render_with_source(&self, source:Option<&str>) -> String {
+		let mut output:String = format!("{}[{}]: {}", self.severity.to_str(), self.code, self.message);
+
+		// Without a span, only the file can be shown.
+		let span:&Span = match &self.span {
+			Some(span) => span,
+			None => {
+				output += &format!("\n --> {}", self.file);
+				self.notes.iter().for_each(|note| output += &format!("\n = note: {note}"));
+				return output;
+			}
+		};
+
+		// Location and snippet.
+		let gutter_width:usize = span.line.to_string().len();
+		let gutter:String = " ".repeat(gutter_width);
+		output += &format!("\n{gutter}--> {}:{}:{}", self.file, span.line, span.column);
+		if let Some(line_text) = source.zip(span.line.checked_sub(1)).and_then(|(source, line_index)| source.split('\n').nth(line_index)).map(|line| line.trim_end_matches('\r')) {
+			let line_chars:Vec<char> = line_text.chars().collect();
+			let caret_start:usize = span.column.saturating_sub(1).min(line_chars.len());
+			let caret_end:usize = (caret_start + source.and_then(|source| source.get(span.start..span.end)).map(|text| text.split('\n').next().unwrap_or_default().chars().count()).unwrap_or(1)).min(line_chars.len());
+			let display_width:fn(&[char]) -> usize = |chars| chars.iter().map(|char| if *char == '\t' { TAB_WIDTH } else { 1 }).sum();
+			output += &format!("\n{gutter} |");
+			output += &format!("\n{} | {}", span.line, line_text.replace('\t', &" ".repeat(TAB_WIDTH)));
+			output += &format!(
+				"\n{gutter} | {}{}{}",
+				" ".repeat(display_width(&line_chars[..caret_start])),
+				"^".repeat(display_width(&line_chars[caret_start..caret_end]).max(1)),
+				self.label.as_ref().map(|label| format!(" {label}")).unwrap_or_default()
+			);
+		}
+
+		// Notes.
+		if !self.notes.is_empty() {
+			output += &format!("\n{gutter} |");
+			self.notes.iter().for_each(|note| output += &format!("\n{gutter} = note: {note}"));
+		}
+		output
+	}
+
+	/// Create a single line of JSON describing the diagnostic, for use by editors and other tools.
+	pub fn to_json_line(&self) -> String {
+		let mut properties:Vec<(&str, String)> = vec![
+			("severity", json_string(self.severity.to_str())),
+			("code", json_string(&self.code)),
+			("message", json_string(&self.message)),
+			("file", json_string(&self.file))
+		];
+		if let Some(span) = &self.span {
+			properties.push(("line", span.line.to_string()));
+			properties.push(("column", span.column.to_string()));
+			properties.push(("start", span.start.to_string()));
+			properties.push(("end", span.end.to_string()));
+		}
+		if let Some(label) = &self.label {
+			properties.push(("label", json_string(label)));
+		}
+		properties.push(("notes", format!("[{}]", self.notes.iter().map(|note| json_string(note)).collect::<Vec<String>>().join(","))));
+		format!("{{{}}}", properties.iter().map(|(name, value)| format!("\"{name}\":{value}")).collect::<Vec<String>>().join(","))
+	}
+}
+
+
+
+/// Create a quoted and escaped JSON string.
+fn json_string(text:&str) -> String {
+	let mut output:String = String::from("\"");
+	for char in text.chars() {
+		match char {
+			'"' => output += "\\\"",
+			'\\' => output += "\\\\",
+			'\n' => output += "\\n",
+			'\r' => output += "\\r",
+			'\t' => output += "\\t",
+			char if (char as u32) < 0x20 => output += &format!("\\u{:04x}", char as u32),
+			char => output.push(char)
+		}
+	}
+	output + "\""
+}
\ No newline at end of file
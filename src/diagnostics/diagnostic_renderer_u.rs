@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ Diagnostic, Span };
+
+
+	fn test_diagnostic() -> Diagnostic {
+		Diagnostic::warning("CM0002", "could not find the file for module `network`", "src/lib.rs")
+			.with_span(Span { start: 30, end: 37, line: 3, column: 5 })
+			.with_label("module declared here")
+			.with_note("expected `network.rs` or `network/mod.rs`")
+	}
+
+
+	#[test]
+	fn test_render_with_source() {
+		let source:&str = "// auto-exports\nmod data;\nmod network;\n";
+		assert_eq!(
+			test_diagnostic().render_with_source(Some(source)),
+			[
+				"warning[CM0002]: could not find the file for module `network`",
+				" --> src/lib.rs:3:5",
+				"  |",
+				"3 | mod network;",
+				"  |     ^^^^^^^ module declared here",
+				"  |",
+				"  = note: expected `network.rs` or `network/mod.rs`"
+			].join("\n")
+		);
+	}
+
+	#[test]
+	fn test_render_without_span() {
+		let diagnostic:Diagnostic = Diagnostic::warning("CM0003", "library `serde` is used but not available", "Cargo.toml");
+		assert_eq!(diagnostic.render_with_source(None), "warning[CM0003]: library `serde` is used but not available\n --> Cargo.toml");
+	}
+
+	#[test]
+	fn test_render_with_invalid_span() {
+
+		// Spans are public, so a span outside of the source only omits the snippet.
+		let diagnostic:Diagnostic = test_diagnostic().with_span(Span { start: 0, end: 0, line: 0, column: 0 });
+		assert_eq!(
+			diagnostic.render_with_source(Some("mod network;\n")),
+			[
+				"warning[CM0002]: could not find the file for module `network`",
+				" --> src/lib.rs:0:0",
+				"  |",
+				"  = note: expected `network.rs` or `network/mod.rs`"
+			].join("\n")
+		);
+		let diagnostic:Diagnostic = test_diagnostic().with_span(Span { start: 0, end: 0, line: 7, column: 1 });
+		assert!(!diagnostic.render_with_source(Some("mod network;\n")).contains(" | mod network;"));
+	}
+
+	#[test]
+	fn test_json_line() {
+		assert_eq!(
+			test_diagnostic().to_json_line(),
+			"{\"severity\":\"warning\",\"code\":\"CM0002\",\"message\":\"could not find the file for module `network`\",\"file\":\"src/lib.rs\",\"line\":3,\"column\":5,\"start\":30,\"end\":37,\"label\":\"module declared here\",\"notes\":[\"expected `network.rs` or `network/mod.rs`\"]}"
+		);
+	}
+}
\ No newline at end of file
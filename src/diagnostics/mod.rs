@@ -0,0 +1,8 @@
+// auto-exports
+#[cfg(test)]
+mod diagnostic_renderer_u;
+mod diagnostic_renderer;
+mod diagnostic;
+
+pub use diagnostic_renderer::*; // render_diagnostics
+pub use diagnostic::*; // DIAGNOSTIC_EXPORT_COLLISION, DIAGNOSTIC_UNRESOLVED_MODULE, DIAGNOSTIC_MISSING_LIBRARY, DIAGNOSTIC_UNUSED_LIBRARY, DIAGNOSTIC_INVALID_MANIFEST, DIAGNOSTIC_UNREADABLE_GIT_CONFIG, Severity, DiagnosticFormat, Diagnostic
\ No newline at end of file
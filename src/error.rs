@@ -0,0 +1,115 @@
+use std::fmt::{ self, Display, Formatter };
+use crate::Span;
+
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+	Io { path:String, message:String },
+	Parse { path:String, span:Option<Span>, message:String },
+	Resolution { path:String, span:Option<Span>, message:String },
+	Manifest { path:String, span:Option<Span>, message:String },
+	Config { path:String, span:Option<Span>, message:String },
+	Undo { path:String, message:String },
+	RollbackFailed { error:Box<Error>, rollback_error:Box<Error> }
+}
+impl Error {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create an I/O error for the given path from any displayable error.
+	pub fn io<T:Display>(path:&str, error:T) -> Error {
+		Error::Io { path: path.to_string(), message: error.to_string() }
+	}
+
+	/// Create a parse error for the given path and location.
+	pub fn parse(path:&str, span:Option<Span>, message:&str) -> Error {
+		Error::Parse { path: path.to_string(), span, message: message.to_string() }
+	}
+
+	/// Create a module resolution error for the given path and location.
+	pub fn resolution(path:&str, span:Option<Span>, message:&str) -> Error {
+		Error::Resolution { path: path.to_string(), span, message: message.to_string() }
+	}
+
+	/// Create a manifest error for the given path and location.
+	pub fn manifest(path:&str, span:Option<Span>, message:&str) -> Error {
+		Error::Manifest { path: path.to_string(), span, message: message.to_string() }
+	}
+
+	/// Create a configuration error for the given path and location.
+	pub fn config(path:&str, span:Option<Span>, message:&str) -> Error {
+		Error::Config { path: path.to_string(), span, message: message.to_string() }
+	}
+
+
+	/// Create an error for an undo that was refused, for example because the file was edited after the run.
+	pub fn undo(path:&str, message:&str) -> Error {
+		Error::Undo { path: path.to_string(), message: message.to_string() }
+	}
+
+	/// Create an error for a failed operation whose rollback failed as well, meaning files may be partially written.
+	pub fn rollback_failed(error:Error, rollback_error:Error) -> Error {
+		Error::RollbackFailed { error: Box::new(error), rollback_error: Box::new(rollback_error) }
+	}
+
+
+	/* USAGE METHODS */
+
+	/// Get the path of the file the error occurred in.
+	pub fn path(&self) -> &str {
+		match self {
+			Error::Io { path, .. } | Error::Parse { path, .. } | Error::Resolution { path, .. } | Error::Manifest { path, .. } | Error::Config { path, .. } | Error::Undo { path, .. } => path,
+			Error::RollbackFailed { error, .. } => error.path()
+		}
+	}
+
+	/// Get the location the error occurred at, if known.
+	pub fn span(&self) -> Option<&Span> {
+		match self {
+			Error::Io { .. } | Error::Undo { .. } => None,
+			Error::Parse { span, .. } | Error::Resolution { span, .. } | Error::Manifest { span, .. } | Error::Config { span, .. } => span.as_ref(),
+			Error::RollbackFailed { error, .. } => error.span()
+		}
+	}
+
+	/// Get the message describing the error, without its kind and location.
+	pub fn message(&self) -> &str {
+		match self {
+			Error::Io { message, .. } | Error::Parse { message, .. } | Error::Resolution { message, .. } | Error::Manifest { message, .. } | Error::Config { message, .. } | Error::Undo { message, .. } => message,
+			Error::RollbackFailed { error, .. } => error.message()
+		}
+	}
+
+	/// Get the name of the kind of error.
+	fn kind_name(&self) -> &str {
+		match self {
+			Error::Io { .. } => "I/O error",
+			Error::Parse { .. } => "parse error",
+			Error::Resolution { .. } => "resolution error",
+			Error::Manifest { .. } => "manifest error",
+			Error::Config { .. } => "config error",
+			Error::Undo { .. } => "undo error",
+			Error::RollbackFailed { error, .. } => error.kind_name()
+		}
+	}
+}
+impl Display for Error {
+	fn fmt(&self, f:&mut Formatter<'_>) -> fmt::Result {
+		if let Error::RollbackFailed { error, rollback_error } = self {
+			return write!(f, "{error}, rolling back failed so files may be partially written: {rollback_error}");
+		}
+		match self.span() {
+			Some(span) => write!(f, "{} in {}:{}:{}: {}", self.kind_name(), self.path(), span.line, span.column, self.message()),
+			None => write!(f, "{} in {}: {}", self.kind_name(), self.path(), self.message())
+		}
+	}
+}
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Error::RollbackFailed { rollback_error, .. } => Some(rollback_error.as_ref()),
+			_ => None
+		}
+	}
+}
\ No newline at end of file
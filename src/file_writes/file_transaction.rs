@@ -0,0 +1,165 @@
+use crate::{ Error, UndoJournal };
+
+
+
+const TEMP_FILE_SUFFIX:&str = ".crate_manager_tmp";
+
+
+
+struct PlannedWrite {
+	path:String,
+	contents:Option<String>,
+	original:Option<String>
+}
+
+
+
+#[derive(Default)]
+pub struct FileTransaction {
+	writes:Vec<PlannedWrite>,
+	committed:Vec<PlannedWrite>,
+	undo_journal:Option<UndoJournal>
+}
+impl FileTransaction {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new empty transaction.
+	pub fn new() -> FileTransaction {
+		FileTransaction::default()
+	}
+
+	/// Return self with an undo journal. A successful commit that writes any files is recorded in the journal, so it can be undone later.
+	pub fn with_undo_journal(mut self, undo_journal:UndoJournal) -> Self {
+		self.undo_journal = Some(undo_journal);
+		self
+	}
+
+
+	/* USAGE METHODS */
+
+	/// Stage new contents for a file. The original contents are restored if committing the transaction fails, a file without original contents is removed instead. Staging the same file again replaces its planned contents.
+	pub fn stage(&mut self, path:&str, contents:String, original:Option<String>) {
+		self.stage_change(path, Some(contents), original);
+	}
+
+	/// Stage the removal of a file. The original contents are restored if committing the transaction fails.
+	pub fn stage_removal(&mut self, path:&str, original:String) {
+		self.stage_change(path, None, Some(original));
+	}
+
+	/// Stage new contents or the removal of a file.
+	fn stage_change(&mut self, path:&str, contents:Option<String>, original:Option<String>) {
+		match self.writes.iter_mut().find(|write| write.path == path) {
+			Some(write) => write.contents = contents,
+			None => self.writes.push(PlannedWrite { path: path.to_string(), contents, original })
+		}
+	}
+
+	/// Get the paths of all staged files.
+	pub fn paths(&self) -> Vec<&str> {
+		self.writes.iter().map(|write| write.path.as_str()).collect()
+	}
+
+	/// Whether or not no files are staged.
+	pub fn is_empty(&self) -> bool {
+		self.writes.is_empty()
+	}
+
+	/// Write all staged files, creating missing parent directories. All contents are first written to temporary files, which then replace the original files. Staged removals happen in the same step. If anything fails, all files, including those of earlier committed steps, are restored to their original contents. Records all written files in the undo journal, if there is one.
+	pub fn commit(mut self) -> Result<(), Error> {
+		self.commit_step()?;
+
+		// Record the run.
+		if let Some(undo_journal) = self.undo_journal.as_ref().filter(|_| !self.committed.is_empty()) {
+			undo_journal.record(&self.committed.iter().map(|write| (write.path.as_str(), write.contents.as_deref(), write.original.as_deref())).collect::<Vec<(&str, Option<&str>, Option<&str>)>>())?;
+		}
+		Ok(())
+	}
+
+	/// Write all staged files like a commit, but keep the transaction open for further steps. The written files are only recorded in the undo journal once the transaction is committed, so all steps can be undone at once. Failing to commit or rolling back later also restores the files of this step.
+	pub fn commit_step(&mut self) -> Result<(), Error> {
+
+		// Write all temporary files.
+		let mut temp_files:Vec<String> = Vec::new();
+		for write in &self.writes {
+			let temp_file:String = write.path.clone() + TEMP_FILE_SUFFIX;
+			if let Some(contents) = &write.contents {
+				if let Err(error) = Self::create_parent_dir(&write.path).and_then(|_| std::fs::write(&temp_file, contents)) {
+					Self::remove_files(&temp_files);
+					let _ = std::fs::remove_file(&temp_file);
+					return Err(self.rolled_back(&[], &write.path, error));
+				}
+			}
+			temp_files.push(temp_file);
+		}
+
+		// Replace or remove the original files.
+		for (index, write) in self.writes.iter().enumerate() {
+			let result:std::io::Result<()> = match write.contents {
+				Some(_) => std::fs::rename(&temp_files[index], &write.path),
+				None => std::fs::remove_file(&write.path)
+			};
+			if let Err(error) = result {
+				Self::remove_files(&temp_files[index..]);
+				return Err(self.rolled_back(&self.writes[..index], &write.path, error));
+			}
+		}
+
+		// Keep the written files, merging them with earlier steps. A file keeps the original contents from before the first step.
+		for write in std::mem::take(&mut self.writes) {
+			match self.committed.iter_mut().find(|committed| committed.path == write.path) {
+				Some(committed) => committed.contents = write.contents,
+				None => self.committed.push(write)
+			}
+		}
+		Ok(())
+	}
+
+	/// Restore all files written by earlier committed steps to their original contents. Staged files that were not written yet are discarded.
+	pub fn rollback(self) -> Result<(), Error> {
+		let rollback_errors:Vec<String> = self.committed.iter().rev().filter_map(|write| Self::restore(write).err()).collect();
+		if rollback_errors.is_empty() {
+			Ok(())
+		} else {
+			Err(Error::io(&self.committed[0].path, format!("rolling back failed: {}", rollback_errors.join(", "))))
+		}
+	}
+
+	/// Restore the given written files of the current step and all files of earlier committed steps after an error. Returns the error, including any errors while restoring.
+	fn rolled_back(&self, written:&[PlannedWrite], path:&str, error:std::io::Error) -> Error {
+		let rollback_errors:Vec<String> = written.iter().rev().chain(self.committed.iter().rev()).filter_map(|write| Self::restore(write).err()).collect();
+		if rollback_errors.is_empty() {
+			Error::io(path, error)
+		} else {
+			Error::io(path, format!("{error}, rolling back failed: {}", rollback_errors.join(", ")))
+		}
+	}
+
+	/// Restore a file to its original contents.
+	fn restore(write:&PlannedWrite) -> Result<(), String> {
+		let result:std::io::Result<()> = match &write.original {
+			Some(original) => {
+				let temp_file:String = write.path.clone() + TEMP_FILE_SUFFIX;
+				Self::create_parent_dir(&write.path).and_then(|_| std::fs::write(&temp_file, original)).and_then(|_| std::fs::rename(&temp_file, &write.path))
+			},
+			None => std::fs::remove_file(&write.path)
+		};
+		result.map_err(|error| format!("{}: {error}", write.path))
+	}
+
+	/// Create the parent directory of a file if it does not exist yet.
+	fn create_parent_dir(path:&str) -> std::io::Result<()> {
+		match std::path::Path::new(path).parent().filter(|dir| !dir.as_os_str().is_empty()) {
+			Some(dir) => std::fs::create_dir_all(dir),
+			None => Ok(())
+		}
+	}
+
+	/// Remove files, ignoring any errors.
+	fn remove_files(files:&[String]) {
+		for file in files {
+			let _ = std::fs::remove_file(file);
+		}
+	}
+}
\ No newline at end of file
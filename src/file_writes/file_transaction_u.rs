@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+	use crate::FileTransaction;
+
+
+	#[test]
+	fn test_commit_writes_all_files() {
+		const TEST_DIR:&str = "target/crate_manager/test_transaction_commit";
+		let _ = std::fs::remove_dir_all(TEST_DIR);
+		std::fs::create_dir_all(TEST_DIR).unwrap();
+		std::fs::write(format!("{TEST_DIR}/a.rs"), "old a").unwrap();
+
+		let mut transaction:FileTransaction = FileTransaction::new();
+		transaction.stage(&format!("{TEST_DIR}/a.rs"), "new a".to_string(), Some("old a".to_string()));
+		transaction.stage(&format!("{TEST_DIR}/b.rs"), "new b".to_string(), None);
+		transaction.commit().unwrap();
+
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/a.rs")).unwrap(), "new a");
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/b.rs")).unwrap(), "new b");
+		assert_eq!(std::fs::read_dir(TEST_DIR).unwrap().count(), 2);
+		std::fs::remove_dir_all(TEST_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_failed_commit_rolls_back() {
+		const TEST_DIR:&str = "target/crate_manager/test_transaction_rollback";
+		let _ = std::fs::remove_dir_all(TEST_DIR);
+		std::fs::create_dir_all(format!("{TEST_DIR}/c.rs/nested")).unwrap();
+		std::fs::write(format!("{TEST_DIR}/a.rs"), "old a").unwrap();
+
+		// Replacing a non-empty directory with a file fails after the first file was already replaced.
+		let mut transaction:FileTransaction = FileTransaction::new();
+		transaction.stage(&format!("{TEST_DIR}/a.rs"), "new a".to_string(), Some("old a".to_string()));
+		transaction.stage(&format!("{TEST_DIR}/b.rs"), "new b".to_string(), None);
+		transaction.stage(&format!("{TEST_DIR}/c.rs"), "new c".to_string(), None);
+		assert!(transaction.commit().is_err());
+
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/a.rs")).unwrap(), "old a");
+		assert!(!std::path::Path::new(&format!("{TEST_DIR}/b.rs")).exists());
+		assert_eq!(std::fs::read_dir(TEST_DIR).unwrap().count(), 2);
+		std::fs::remove_dir_all(TEST_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_steps_are_rolled_back_together() {
+		const TEST_DIR:&str = "target/crate_manager/test_transaction_steps";
+		let _ = std::fs::remove_dir_all(TEST_DIR);
+		std::fs::create_dir_all(TEST_DIR).unwrap();
+		std::fs::write(format!("{TEST_DIR}/a.rs"), "old a").unwrap();
+
+		// The first step is written immediately.
+		let mut transaction:FileTransaction = FileTransaction::new();
+		transaction.stage(&format!("{TEST_DIR}/a.rs"), "new a".to_string(), Some("old a".to_string()));
+		transaction.stage(&format!("{TEST_DIR}/nested/b.rs"), "new b".to_string(), None);
+		transaction.commit_step().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/a.rs")).unwrap(), "new a");
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/nested/b.rs")).unwrap(), "new b");
+
+		// Rolling back restores the files of all steps.
+		transaction.stage(&format!("{TEST_DIR}/a.rs"), "newer a".to_string(), Some("new a".to_string()));
+		transaction.rollback().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/a.rs")).unwrap(), "old a");
+		assert!(!std::path::Path::new(&format!("{TEST_DIR}/nested/b.rs")).exists());
+		std::fs::remove_dir_all(TEST_DIR).unwrap();
+	}
+}
\ No newline at end of file
@@ -0,0 +1,10 @@
+// auto-exports
+#[cfg(test)]
+mod file_transaction_u;
+mod file_transaction;
+#[cfg(test)]
+mod undo_journal_u;
+mod undo_journal;
+
+pub use file_transaction::*; // FileTransaction
+pub use undo_journal::*; // DEFAULT_UNDO_JOURNAL_DIR, UndoJournal
\ No newline at end of file
@@ -0,0 +1,151 @@
+use crate::{ content_hash, join_fields, split_fields, Error, FileTransaction };
+use std::time::{ Duration, SystemTime, UNIX_EPOCH };
+use file_ref::FileRef;
+
+
+
+pub const DEFAULT_UNDO_JOURNAL_DIR:&str = "target/crate_manager/undo";
+const UNDO_JOURNAL_FILE_NAME:&str = "journal.txt";
+const TEMP_JOURNAL_FILE_SUFFIX:&str = ".tmp";
+const BACKUP_FILE_PREFIX:&str = "backup_";
+const UNDO_JOURNAL_HEADER:&str = "crate_manager undo journal";
+
+
+
+struct JournalEntry {
+	path:String,
+	original_hash:Option<u64>,
+	new_hash:Option<u64>,
+	backup_file:Option<String>
+}
+
+
+
+#[derive(Clone)]
+pub struct UndoJournal {
+	dir:String
+}
+impl UndoJournal {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new undo journal stored in the given directory. Only the last run that modified files is kept.
+	pub fn new(dir:&str) -> UndoJournal {
+		UndoJournal {
+			dir: dir.trim_end_matches('/').to_string()
+		}
+	}
+
+
+	/* USAGE METHODS */
+
+	/// Record a run that wrote the given files, replacing the previously recorded run. Takes the path, new contents and original contents of each file, files without original contents were created by the run and files without new contents were removed. The new journal is written next to the previous one and renamed into place, so an interrupted record keeps the previous run intact.
+	pub(crate) fn record(&self, writes:&[(&str, Option<&str>, Option<&str>)]) -> Result<(), Error> {
+		std::fs::create_dir_all(&self.dir).map_err(|error| Error::io(&self.dir, error))?;
+
+		// Write backups, named by run so they do not replace the backups of the previous run.
+		let now:Duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+		let run_id:String = format!("{:x}", now.as_nanos());
+		let mut lines:Vec<String> = vec![join_fields(&[UNDO_JOURNAL_HEADER, &now.as_secs().to_string()])];
+		let mut backup_files:Vec<String> = Vec::new();
+		for (index, (path, new_contents, original)) in writes.iter().enumerate() {
+			let backup_file:String = match original {
+				Some(original) => {
+					let backup_file:String = format!("{BACKUP_FILE_PREFIX}{run_id}_{index}.txt");
+					let backup_path:String = format!("{}/{backup_file}", self.dir);
+					FileRef::new(&backup_path).write(original.to_string()).map_err(|error| Error::io(&backup_path, error))?;
+					backup_files.push(backup_file.clone());
+					backup_file
+				},
+				None => String::new()
+			};
+			let original_hash:String = original.map(|original| format!("{:x}", content_hash(original))).unwrap_or_default();
+			let new_hash:String = new_contents.map(|new_contents| format!("{:x}", content_hash(new_contents))).unwrap_or_default();
+			lines.push(join_fields(&["F", path, &original_hash, &new_hash, &backup_file]));
+		}
+
+		// Write the journal to a temporary file and rename it over the previous journal.
+		let journal_file:String = self.journal_file();
+		let temp_journal_file:String = journal_file.clone() + TEMP_JOURNAL_FILE_SUFFIX;
+		FileRef::new(&temp_journal_file).write(lines.join("\n")).map_err(|error| Error::io(&temp_journal_file, error))?;
+		std::fs::rename(&temp_journal_file, &journal_file).map_err(|error| Error::io(&journal_file, error))?;
+
+		// Remove backups of previous runs.
+		if let Ok(entries) = std::fs::read_dir(&self.dir) {
+			for entry in entries.flatten() {
+				let file_name:String = entry.file_name().to_string_lossy().to_string();
+				if file_name.starts_with(BACKUP_FILE_PREFIX) && !backup_files.contains(&file_name) {
+					let _ = std::fs::remove_file(entry.path());
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Get the time the last recorded run finished, in seconds since the unix epoch. Returns None if there is no recorded run.
+	pub fn timestamp(&self) -> Option<u64> {
+		let contents:String = FileRef::new(&self.journal_file()).read().ok()?;
+		split_fields(contents.split('\n').next()?).get(1)?.parse().ok()
+	}
+
+	/// Restore all files modified or removed by the last recorded run to their previous contents and remove files it created, all in a single transaction. Refuses to undo anything if any of the files was edited since. Returns the paths of all restored files.
+	pub fn undo(&self) -> Result<Vec<String>, Error> {
+		let entries:Vec<JournalEntry> = self.load()?;
+
+		// Make sure no file was edited since.
+		let mut current_contents:Vec<Option<String>> = Vec::new();
+		for entry in &entries {
+			let current:Option<String> = FileRef::new(&entry.path).read().ok();
+			if current.as_deref().map(content_hash) != entry.new_hash {
+				return Err(Error::undo(&entry.path, "file was edited after the last run, refusing to undo"));
+			}
+			current_contents.push(current);
+		}
+
+		// Restore original contents and remove created files.
+		let mut transaction:FileTransaction = FileTransaction::new();
+		for (entry, current) in entries.iter().zip(current_contents) {
+			if let (Some(backup_file), Some(original_hash)) = (&entry.backup_file, entry.original_hash) {
+				let backup_path:String = format!("{}/{backup_file}", self.dir);
+				let original:String = FileRef::new(&backup_path).read().map_err(|error| Error::io(&backup_path, error))?;
+				if content_hash(&original) != original_hash {
+					return Err(Error::undo(&backup_path, "backup does not match the recorded contents"));
+				}
+				transaction.stage(&entry.path, original, current);
+			} else if let Some(current) = current {
+				transaction.stage_removal(&entry.path, current);
+			}
+		}
+		transaction.commit()?;
+		std::fs::remove_dir_all(&self.dir).map_err(|error| Error::io(&self.dir, error))?;
+		Ok(entries.into_iter().map(|entry| entry.path).collect())
+	}
+
+	/// Load the entries of the journal file.
+	fn load(&self) -> Result<Vec<JournalEntry>, Error> {
+		let journal_file:String = self.journal_file();
+		let contents:String = FileRef::new(&journal_file).read().map_err(|_| Error::undo(&journal_file, "there is no recorded run to undo"))?;
+		let mut lines = contents.split('\n');
+		if split_fields(lines.next().unwrap_or_default()).first().map(|header| header.as_str()) != Some(UNDO_JOURNAL_HEADER) {
+			return Err(Error::io(&journal_file, "invalid undo journal header"));
+		}
+		lines.filter(|line| !line.is_empty()).map(|line| {
+			let fields:Vec<String> = split_fields(line);
+			let parse_hash = |field:&str| u64::from_str_radix(field, 16).map_err(|_| Error::io(&journal_file, format!("invalid hash in undo journal line \"{line}\"")));
+			if fields.len() != 5 || fields[0] != "F" {
+				return Err(Error::io(&journal_file, format!("invalid undo journal line \"{line}\"")));
+			}
+			Ok(JournalEntry {
+				path: fields[1].clone(),
+				original_hash: if fields[2].is_empty() { None } else { Some(parse_hash(&fields[2])?) },
+				new_hash: if fields[3].is_empty() { None } else { Some(parse_hash(&fields[3])?) },
+				backup_file: if fields[4].is_empty() { None } else { Some(fields[4].clone()) }
+			})
+		}).collect()
+	}
+
+	/// Get the path of the journal file.
+	fn journal_file(&self) -> String {
+		format!("{}/{UNDO_JOURNAL_FILE_NAME}", self.dir)
+	}
+}
\ No newline at end of file
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ Error, FileTransaction, UndoJournal };
+
+
+	#[test]
+	fn test_undo_restores_previous_state() {
+		const TEST_DIR:&str = "target/crate_manager/test_undo_restore";
+		let _ = std::fs::remove_dir_all(TEST_DIR);
+		std::fs::create_dir_all(TEST_DIR).unwrap();
+		std::fs::write(format!("{TEST_DIR}/a.rs"), "old a").unwrap();
+
+		// Commit a run.
+		let undo_journal:UndoJournal = UndoJournal::new(&format!("{TEST_DIR}/journal"));
+		let mut transaction:FileTransaction = FileTransaction::new().with_undo_journal(undo_journal.clone());
+		transaction.stage(&format!("{TEST_DIR}/a.rs"), "new a".to_string(), Some("old a".to_string()));
+		transaction.stage(&format!("{TEST_DIR}/b.rs"), "new b".to_string(), None);
+		transaction.commit().unwrap();
+		assert!(undo_journal.timestamp().is_some());
+
+		// Undo the run.
+		assert_eq!(undo_journal.undo().unwrap().len(), 2);
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/a.rs")).unwrap(), "old a");
+		assert!(!std::path::Path::new(&format!("{TEST_DIR}/b.rs")).exists());
+		assert!(undo_journal.timestamp().is_none());
+		assert!(undo_journal.undo().is_err());
+		std::fs::remove_dir_all(TEST_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_record_replaces_previous_run() {
+		const TEST_DIR:&str = "target/crate_manager/test_undo_replace";
+		let _ = std::fs::remove_dir_all(TEST_DIR);
+		std::fs::create_dir_all(TEST_DIR).unwrap();
+		std::fs::write(format!("{TEST_DIR}/a.rs"), "old a").unwrap();
+
+		// Commit two runs.
+		let undo_journal:UndoJournal = UndoJournal::new(&format!("{TEST_DIR}/journal"));
+		for (original, contents) in [("old a", "new a"), ("new a", "newer a")] {
+			let mut transaction:FileTransaction = FileTransaction::new().with_undo_journal(undo_journal.clone());
+			transaction.stage(&format!("{TEST_DIR}/a.rs"), contents.to_string(), Some(original.to_string()));
+			transaction.commit().unwrap();
+		}
+
+		// Only the journal and the backup of the last run are kept.
+		let mut journal_files:Vec<String> = std::fs::read_dir(format!("{TEST_DIR}/journal")).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().to_string()).collect();
+		journal_files.sort();
+		assert_eq!(journal_files.len(), 2);
+		assert!(journal_files[0].starts_with("backup_"));
+		assert_eq!(journal_files[1], "journal.txt");
+
+		// Undoing restores the state before the last run.
+		undo_journal.undo().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/a.rs")).unwrap(), "new a");
+		std::fs::remove_dir_all(TEST_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_undo_refuses_edited_files() {
+		const TEST_DIR:&str = "target/crate_manager/test_undo_refuse";
+		let _ = std::fs::remove_dir_all(TEST_DIR);
+		std::fs::create_dir_all(TEST_DIR).unwrap();
+		std::fs::write(format!("{TEST_DIR}/a.rs"), "old a").unwrap();
+		std::fs::write(format!("{TEST_DIR}/b.rs"), "old b").unwrap();
+
+		// Commit a run and edit one of the files afterwards.
+		let undo_journal:UndoJournal = UndoJournal::new(&format!("{TEST_DIR}/journal"));
+		let mut transaction:FileTransaction = FileTransaction::new().with_undo_journal(undo_journal.clone());
+		transaction.stage(&format!("{TEST_DIR}/a.rs"), "new a".to_string(), Some("old a".to_string()));
+		transaction.stage(&format!("{TEST_DIR}/b.rs"), "new b".to_string(), Some("old b".to_string()));
+		transaction.commit().unwrap();
+		std::fs::write(format!("{TEST_DIR}/b.rs"), "edited b").unwrap();
+
+		// Undoing should not touch any file.
+		assert!(matches!(undo_journal.undo(), Err(Error::Undo { .. })));
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/a.rs")).unwrap(), "new a");
+		assert_eq!(std::fs::read_to_string(format!("{TEST_DIR}/b.rs")).unwrap(), "edited b");
+		std::fs::remove_dir_all(TEST_DIR).unwrap();
+	}
+}
\ No newline at end of file
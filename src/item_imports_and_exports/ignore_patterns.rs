@@ -0,0 +1,169 @@
+use std::path::Path;
+use file_ref::FileRef;
+
+
+
+pub const IGNORE_FILE_NAME:&str = ".crate_manager_ignore";
+pub const GITIGNORE_FILE_NAME:&str = ".gitignore";
+
+
+
+#[derive(Clone, Debug, PartialEq)]
+struct IgnoreRule {
+	base_dir:String,
+	pattern:Vec<char>,
+	negated:bool,
+	dir_only:bool,
+	anchored:bool
+}
+impl IgnoreRule {
+
+	/// Parse a rule from a line of an ignore file. Returns None for empty lines and comments.
+	fn parse(base_dir:&str, line:&str) -> Option<IgnoreRule> {
+		let mut pattern:&str = line.trim_end_matches(['\r', '\n']).trim_end_matches(' ');
+		if pattern.is_empty() || pattern.starts_with('#') {
+			return None;
+		}
+		let negated:bool = pattern.starts_with('!');
+		if negated || pattern.starts_with("\\!") || pattern.starts_with("\\#") {
+			pattern = &pattern[1..];
+		}
+		let dir_only:bool = pattern.ends_with('/');
+		let pattern:&str = pattern.trim_end_matches('/');
+		let anchored:bool = pattern.contains('/');
+		let pattern:&str = pattern.trim_start_matches('/');
+		if pattern.is_empty() {
+			return None;
+		}
+		Some(IgnoreRule {
+			base_dir: normalize_path(base_dir).trim_end_matches('/').to_string(),
+			pattern: pattern.chars().collect(),
+			negated,
+			dir_only,
+			anchored
+		})
+	}
+
+	/// Whether or not the rule matches the given path.
+	fn matches(&self, path:&str, is_dir:bool) -> bool {
+		if self.dir_only && !is_dir {
+			return false;
+		}
+		let relative_path:&str = match path.strip_prefix(&self.base_dir).and_then(|path| path.strip_prefix('/')) {
+			Some(relative_path) => relative_path,
+			None => return false
+		};
+		let text:Vec<char> = if self.anchored { relative_path.chars().collect() } else { relative_path.rsplit('/').next().unwrap_or_default().chars().collect() };
+		glob_matches(&self.pattern, &text)
+	}
+}
+
+
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IgnorePatterns {
+	rules:Vec<IgnoreRule>
+}
+impl IgnorePatterns {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new empty set of ignore patterns.
+	pub fn new() -> IgnorePatterns {
+		IgnorePatterns::default()
+	}
+
+	/// Load the ignore files with the given names from a directory and all its parent directories, up to the root of the git repository. Rules in deeper directories take precedence.
+	pub fn load(dir:&str, ignore_file_names:&[&str]) -> IgnorePatterns {
+		let mut dirs:Vec<&Path> = Vec::new();
+		for dir in Path::new(dir).ancestors() {
+			dirs.push(dir);
+			if dir.join(".git").exists() {
+				break;
+			}
+		}
+		let mut ignore_patterns:IgnorePatterns = IgnorePatterns::new();
+		for dir in dirs.into_iter().rev() {
+			for ignore_file_name in ignore_file_names {
+				if let Ok(contents) = FileRef::new(&dir.join(ignore_file_name).to_string_lossy()).read() {
+					ignore_patterns = ignore_patterns.with_patterns(&dir.to_string_lossy(), &contents.lines().collect::<Vec<&str>>());
+				}
+			}
+		}
+		ignore_patterns
+	}
+
+	/// Return self with additional patterns, relative to the given base directory. Uses the same syntax as lines in a .gitignore file. Added patterns take precedence over existing ones.
+	pub fn with_patterns(mut self, base_dir:&str, patterns:&[&str]) -> Self {
+		self.rules.extend(patterns.iter().filter_map(|pattern| IgnoreRule::parse(base_dir, pattern)));
+		self
+	}
+
+	/// Return self with all patterns of another set added. The added patterns take precedence over existing ones.
+	pub fn merged_with(mut self, other:&IgnorePatterns) -> Self {
+		self.rules.extend(other.rules.iter().cloned());
+		self
+	}
+
+
+	/* USAGE METHODS */
+
+	/// Whether or not the given path is ignored. The last matching pattern decides, negated patterns un-ignore a path.
+	pub fn is_ignored(&self, path:&str, is_dir:bool) -> bool {
+		let path:String = normalize_path(path);
+		self.rules.iter().rev().find(|rule| rule.matches(&path, is_dir)).is_some_and(|rule| !rule.negated)
+	}
+}
+
+
+
+/// Normalize a path to use forward slashes.
+fn normalize_path(path:&str) -> String {
+	path.replace('\\', "/")
+}
+
+/// Whether or not the text matches a glob pattern. A `*` matches anything except a slash, a `?` matches a single character except a slash, `**` matches across directories and `[abc]` or `[a-z]` matches a set of characters.
+fn glob_matches(pattern:&[char], text:&[char]) -> bool {
+	match pattern.first() {
+		None => text.is_empty(),
+		Some('*') if pattern.get(1) == Some(&'*') => {
+			match pattern.get(2) {
+				Some('/') => glob_matches(&pattern[3..], text) || (0..text.len()).any(|index| text[index] == '/' && glob_matches(&pattern[3..], &text[index + 1..])),
+				_ => (0..=text.len()).any(|index| glob_matches(&pattern[2..], &text[index..]))
+			}
+		},
+		Some('*') => {
+			let segment_length:usize = text.iter().position(|char| *char == '/').unwrap_or(text.len());
+			(0..=segment_length).any(|index| glob_matches(&pattern[1..], &text[index..]))
+		},
+		Some('?') => !text.is_empty() && text[0] != '/' && glob_matches(&pattern[1..], &text[1..]),
+		Some('[') => {
+			let class_end:usize = match pattern.iter().skip(2).position(|char| *char == ']') {
+				Some(position) => position + 2,
+				None => return text.first() == Some(&'[') && glob_matches(&pattern[1..], &text[1..])
+			};
+			let (negated, class):(bool, &[char]) = match pattern[1] {
+				'!' | '^' => (true, &pattern[2..class_end]),
+				_ => (false, &pattern[1..class_end])
+			};
+			let char:char = match text.first() {
+				Some(char) if *char != '/' => *char,
+				_ => return false
+			};
+			let mut in_class:bool = false;
+			let mut index:usize = 0;
+			while index < class.len() {
+				if index + 2 < class.len() && class[index + 1] == '-' {
+					in_class |= class[index] <= char && char <= class[index + 2];
+					index += 3;
+				} else {
+					in_class |= class[index] == char;
+					index += 1;
+				}
+			}
+			in_class != negated && glob_matches(&pattern[class_end + 1..], &text[1..])
+		},
+		Some('\\') if pattern.len() > 1 => text.first() == Some(&pattern[1]) && glob_matches(&pattern[2..], &text[1..]),
+		Some(char) => text.first() == Some(char) && glob_matches(&pattern[1..], &text[1..])
+	}
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ IgnorePatterns, ItemImportExportUpdater };
+
+
+	#[test]
+	fn test_pattern_matching() {
+		let ignore_patterns:IgnorePatterns = IgnorePatterns::new().with_patterns("/crate/src", &[
+			"# comment",
+			"*_scratch.rs",
+			"/generated/",
+			"platform/**/windows.rs",
+			"temp?.rs",
+			"[ab]_old.rs",
+			"keep/",
+			"!keep_scratch.rs"
+		]);
+		assert!(ignore_patterns.is_ignored("/crate/src/module/test_scratch.rs", false));
+		assert!(!ignore_patterns.is_ignored("/crate/src/keep_scratch.rs", false));
+		assert!(ignore_patterns.is_ignored("/crate/src/generated", true));
+		assert!(!ignore_patterns.is_ignored("/crate/src/generated", false));
+		assert!(!ignore_patterns.is_ignored("/crate/src/module/generated", true));
+		assert!(ignore_patterns.is_ignored("/crate/src/platform/windows.rs", false));
+		assert!(ignore_patterns.is_ignored("/crate/src/platform/os/desktop/windows.rs", false));
+		assert!(ignore_patterns.is_ignored("/crate/src/temp1.rs", false));
+		assert!(!ignore_patterns.is_ignored("/crate/src/temp12.rs", false));
+		assert!(ignore_patterns.is_ignored("/crate/src/b_old.rs", false));
+		assert!(!ignore_patterns.is_ignored("/crate/src/c_old.rs", false));
+		assert!(ignore_patterns.is_ignored("/crate/src/module/keep", true));
+		assert!(!ignore_patterns.is_ignored("/other/test_scratch.rs", false));
+	}
+
+	#[test]
+	fn test_directory_scan_skips_ignored() {
+		const CRATE_DIR:&str = "target/crate_manager/test_ignore_crate";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(format!("{CRATE_DIR}/generated")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/a.rs"), "pub fn function_a() {}").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/scratch.rs"), "pub fn scratch() {}").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/local.rs"), "pub fn local() {}").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/generated/mod.rs"), "pub fn generated() {}").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/.gitignore"), "generated/\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/.crate_manager_ignore"), "scratch.rs\n").unwrap();
+
+		ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_ignore_patterns(&["local.rs"]).generate().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\nmod a;\n\npub use a::*; // function_a");
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+}
\ No newline at end of file
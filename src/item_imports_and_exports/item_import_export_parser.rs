@@ -1,5 +1,5 @@
 use glyph_kit::{ TextMatchResult, TextMatcher, TextMatcherSet, TextMatcherSource };
-use crate::item_imports_and_exports::AUTO_EXPORTS_TAG;
+use crate::{ item_imports_and_exports::AUTO_EXPORTS_TAG, block_length, token_length, Error, Export, Import, LineIndex, ParsedFile, PubType, Span, TokenKind };
 use cachew::cache;
 
 
@@ -10,6 +10,46 @@ pub const PARSER_PUB_TYPE_TAG:&str = "pub_type";
 pub const PARSER_TYPE_TAG:&str = "export_type";
 pub const PARSER_IDENTIFIER_TAG:&str = "identifier";
 pub const PARSER_AUTO_EXPORTS_TRIGGER_TAG:&str = "auto_exports_trigger";
+
+
+
+/// Parse all imports, exports and the location of the auto-exports trigger from the contents of a file.
+pub(crate) fn parse_file_contents(path:&str, contents:&str) -> Result<ParsedFile, Error> {
+	let mut parsed_file:ParsedFile = ParsedFile { imports: Vec::new(), exports: Vec::new(), auto_exports_trigger: None };
+	let line_index:LineIndex = LineIndex::new(contents);
+	for (match_cursor, match_result) in imports_exports_parser().find_matches(contents) {
+
+		// Imports and exports.
+		if match_result.type_name == MODULE_IMPORT_TAG || match_result.type_name == PARSER_EXPORT_TAG {
+			let match_end:usize = match_cursor + match_result.length;
+			let pub_type:Option<PubType> = match_result.find_child_by_type_path(&[PARSER_PUB_TYPE_TAG]).map(|child| PubType::from_str(&child.contents));
+			let struct_type:String = match match_result.find_child_by_type_path(&[PARSER_TYPE_TAG]) {
+				Some(child) => child.contents.clone(),
+				None => return Err(Error::parse(path, Some(line_index.span(contents, match_cursor, match_end)), "item is missing its type"))
+			};
+			let identifier:String = match match_result.find_child_by_type_path(&[PARSER_IDENTIFIER_TAG]) {
+				Some(child) => child.contents.clone(),
+				None => return Err(Error::parse(path, Some(line_index.span(contents, match_cursor, match_end)), &format!("{struct_type} is missing its identifier")))
+			};
+			let span:Span = line_index.span(contents, match_end.saturating_sub(identifier.len()).max(match_cursor), match_end);
+
+			if match_result.type_name == MODULE_IMPORT_TAG {
+				parsed_file.imports.push(Import { pub_type, struct_type, identifier, span });
+			} else if pub_type.is_some() {
+				parsed_file.exports.push(Export { pub_type, struct_type, identifier, span });
+			}
+		}
+
+		// Auto-exporting trigger.
+		else if match_result.type_name == PARSER_AUTO_EXPORTS_TRIGGER_TAG {
+			parsed_file.auto_exports_trigger = Some(match_cursor);
+		}
+	}
+	Ok(parsed_file)
+}
+
+
+
 pub fn imports_exports_parser() -> &'static TextMatcherSet {
 	cache!(
 		TextMatcherSet,
@@ -29,10 +69,6 @@ pub fn imports_exports_parser() -> &'static TextMatcherSet {
 				None
 			})));
 
-			// Full matchers.
-			let string_matcher:TextMatcher = TextMatcher::new("\"") + TextMatcher::optional_repeat_max(TextMatcher::new("\\\"") | !TextMatcher::new("\"")) + "\"";
-			let comment_matcher:TextMatcher = (TextMatcher::new("//") + TextMatcher::optional_repeat_max(!TextMatcher::new("\n")) + "\n") | (TextMatcher::new("/*") + TextMatcher::optional_repeat_max(!TextMatcher::new("*/")) + "*/");
-			
 			// Matcher set.
 			TextMatcherSet::new().with_matchers(vec![
 
@@ -59,7 +95,7 @@ pub fn imports_exports_parser() -> &'static TextMatcherSet {
 					PARSER_EXPORT_TAG,
 					pub_type_matcher.clone() +
 					TextMatcher::repeat_max(TextMatcher::whitespace()) +
-					TextMatcher::named(PARSER_TYPE_TAG, TextMatcher::new("struct") | "trait" | "fn" | "const" | "static") +
+					TextMatcher::named(PARSER_TYPE_TAG, TextMatcher::new("struct") | "enum" | "trait" | "fn" | "const" | "static") +
 					TextMatcher::repeat_max(TextMatcher::whitespace()) +
 					identifier_matcher.clone()
 				),
@@ -75,37 +111,17 @@ pub fn imports_exports_parser() -> &'static TextMatcherSet {
 
 
 				/* MISCELLANEOUS */
-				("string", string_matcher.clone()),
-				("comment", comment_matcher.clone()),
+				(
+					"string",
+					TextMatcher::new(|text:&str| token_length(text, TokenKind::Literal).map(|length| TextMatchResult::new(length, text)))
+				),
+				(
+					"comment",
+					TextMatcher::new(|text:&str| token_length(text, TokenKind::Comment).map(|length| TextMatchResult::new(length, text)))
+				),
 				(
 					"scope",
-					TextMatcher::new(
-						move |text:&str| {
-							if !text.is_empty() && &text[..1] == "{" {
-								let mut cursor:usize = 1;
-								let mut depth:usize = 1;
-								let cursor_max:usize = text.len();
-								while depth > 0 && cursor < cursor_max {
-									if let Some(result) = string_matcher.clone().match_text(&text[cursor..]) {
-										cursor += result.length;
-									} else if let Some(result) = comment_matcher.clone().match_text(&text[cursor..]) {
-										cursor += result.length;
-									} else {
-										match &text[cursor..cursor + 1] {
-											"{" => depth += 1,
-											"}" => depth -= 1,
-											_ => {}
-										}
-										cursor += 1;
-									}
-								}
-								if depth == 0 {
-									return Some(TextMatchResult::new(cursor, text))
-								}
-							}
-							None
-						}
-					)
+					TextMatcher::new(|text:&str| block_length(text).map(|length| TextMatchResult::new(length, text)))
 				)
 			])
 		}
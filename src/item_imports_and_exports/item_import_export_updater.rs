@@ -1,133 +1,615 @@
-use crate::{ imports_exports_parser, PubType, Export, Import, item_imports_and_exports::{ AUTO_EXPORTS_TAG }, MODULE_IMPORT_TAG, PARSER_AUTO_EXPORTS_TRIGGER_TAG, PARSER_EXPORT_TAG, PARSER_IDENTIFIER_TAG, PARSER_PUB_TYPE_TAG, PARSER_TYPE_TAG };
-use std::error::Error;
+use crate::{ parse_file_contents, content_hash, CACHE_FORMAT_VERSION, Diagnostic, FileTransaction, IgnorePatterns, UndoJournal, GITIGNORE_FILE_NAME, IGNORE_FILE_NAME, DIAGNOSTIC_EXPORT_COLLISION, DIAGNOSTIC_UNRESOLVED_MODULE, Error, ParseCache, ParsedFile, PubType, Export, Import, item_imports_and_exports::{ AUTO_EXPORTS_TAG } };
+use std::{ cmp::Reverse, path::Path, sync::{ Arc, Mutex, MutexGuard }, thread::{ self, ScopedJoinHandle } };
 use file_ref::FileRef;
 
 
 
+pub const DEFAULT_TEST_FILE_SUFFIX:&str = "_u";
+pub const DEFAULT_CFG_SUFFIXES:&[(&str, &str)] = &[("_linux", "target_os = \"linux\""), ("_windows", "target_os = \"windows\""), ("_macos", "target_os = \"macos\"")];
+pub const DEFAULT_FEATURE_DIR:&str = "features";
+
+
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ModuleScaffolding {
+	#[default]
+	Disabled,
+	ModFile,
+	SiblingFile
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModuleLayout {
+	ModFile,
+	SiblingFile
+}
+
+
+
+#[derive(Clone, Default)]
+struct UpdaterConfig {
+	parse_cache:Option<Arc<Mutex<ParseCache>>>,
+	undo_journal:Option<UndoJournal>,
+	ignore_patterns:IgnorePatterns,
+	skip_gitignore:bool,
+	module_scaffolding:ModuleScaffolding,
+	test_file_suffix:Option<String>,
+	cfg_suffixes:Option<Vec<(String, String)>>,
+	feature_dir:Option<String>,
+	sorted_modules:bool,
+	threads:usize
+}
+impl UpdaterConfig {
+
+	/// Get the amount of threads to parse files with. Defaults to the available parallelism.
+	fn thread_count(&self) -> usize {
+		match self.threads {
+			0 => thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1),
+			threads => threads
+		}
+	}
+
+	/// Get the patterns of files and directories to skip when scanning the given directory. Combines the ignore files in the directory and its parents with the configured patterns.
+	fn ignore_patterns(&self, dir:&str) -> IgnorePatterns {
+		let ignore_file_names:&[&str] = if self.skip_gitignore { &[IGNORE_FILE_NAME] } else { &[GITIGNORE_FILE_NAME, IGNORE_FILE_NAME] };
+		IgnorePatterns::load(dir, ignore_file_names).merged_with(&self.ignore_patterns)
+	}
+
+	/// Get the file name suffix of unit test files.
+	fn test_file_suffix(&self) -> &str {
+		self.test_file_suffix.as_deref().unwrap_or(DEFAULT_TEST_FILE_SUFFIX)
+	}
+
+	/// Whether or not a file only contains unit tests, based on the test file suffix of its name.
+	fn is_test_file(&self, file:&FileRef) -> bool {
+		is_test_file(file, self.test_file_suffix())
+	}
+
+	/// Get the cfg condition a module should be declared behind, based on its file name and location. Test files are gated behind `test`, modules in the feature directory behind the feature with their name and modules with a known suffix behind its mapped condition.
+	fn cfg_condition(&self, file:&FileRef) -> Option<String> {
+		if self.is_test_file(file) {
+			return Some("test".to_string());
+		}
+		let path:&Path = Path::new(file.path());
+		let module_path:&Path = if matches!(file.name(), "lib.rs" | "main.rs" | "mod.rs") { path.parent()? } else { path };
+		let module_name:String = module_path.file_stem()?.to_string_lossy().to_string();
+		let feature_dir:&str = self.feature_dir.as_deref().unwrap_or(DEFAULT_FEATURE_DIR);
+		if !feature_dir.is_empty() && module_path.parent().and_then(|dir| dir.file_name()).is_some_and(|dir_name| dir_name == feature_dir) {
+			return Some(format!("feature = \"{module_name}\""));
+		}
+		match &self.cfg_suffixes {
+			Some(cfg_suffixes) => cfg_suffixes.iter().find(|(suffix, _)| module_name.ends_with(suffix.as_str())).map(|(_, condition)| condition.clone()),
+			None => DEFAULT_CFG_SUFFIXES.iter().find(|(suffix, _)| module_name.ends_with(suffix)).map(|(_, condition)| condition.to_string())
+		}
+	}
+
+	/// Create a new file transaction, recording into the undo journal if there is one.
+	fn transaction(&self) -> FileTransaction {
+		match &self.undo_journal {
+			Some(undo_journal) => FileTransaction::new().with_undo_journal(undo_journal.clone()),
+			None => FileTransaction::new()
+		}
+	}
+
+	/// Create a string identifying the cache format and all settings that influence the generated output. Cached parse results are discarded when this changes.
+	fn fingerprint(&self) -> String {
+		let settings:String = format!(
+			"{CACHE_FORMAT_VERSION}\n{:?}\n{}\n{:?}\n{:?}\n{:?}\n{:?}",
+			self.ignore_patterns, self.skip_gitignore, self.module_scaffolding, self.test_file_suffix, self.cfg_suffixes, self.feature_dir
+		);
+		format!("{:x}", content_hash(&settings))
+	}
+}
+
+
+
 pub struct ItemImportExportUpdater {
+	config:Arc<UpdaterConfig>,
 	file:FileRef,
 	is_mod_file:bool,
+	is_test_file:bool,
+	cfg_condition:Option<String>,
 	parsed:bool,
 	imports:Vec<Import>,
 	exports:[(PubType, Vec<Export>); 3],
-	sub_finders:Vec<ItemImportExportUpdater>
+	sub_finders:Vec<ItemImportExportUpdater>,
+	diagnostics:Vec<Diagnostic>,
+	auto_exports_source:Option<(String, usize)>,
+	scaffolded:bool,
+	rewritten:bool
 }
 impl ItemImportExportUpdater {
 
+	/* CONSTRUCTOR METHODS */
+
 	/// Create a new exports finder.
 	pub fn new(file:&str) -> ItemImportExportUpdater {
+		ItemImportExportUpdater::with_config(file, Arc::new(UpdaterConfig::default()))
+	}
+
+	/// Create a new exports finder that shares the given configuration.
+	fn with_config(file:&str, config:Arc<UpdaterConfig>) -> ItemImportExportUpdater {
 		let file:FileRef = FileRef::new(file).absolute();
+		let is_test_file:bool = config.is_test_file(&file);
+		let cfg_condition:Option<String> = config.cfg_condition(&file);
 		ItemImportExportUpdater {
+			config,
 			file: file.clone(),
-			is_mod_file: file.name() == "lib.rs" || file.name() == "mod.rs",
+			is_mod_file: Self::is_module_file(&file),
+			is_test_file,
+			cfg_condition,
 			parsed: false,
 			imports: Vec::new(),
 			exports: [(PubType::Pub, Vec::new()), (PubType::Crate, Vec::new()), (PubType::Super, Vec::new())],
-			sub_finders: Vec::new()
+			sub_finders: Vec::new(),
+			diagnostics: Vec::new(),
+			auto_exports_source: None,
+			scaffolded: false,
+			rewritten: false
 		}
 	}
 
-	/// Find all imports and exports for this file.
-	pub fn generate(&mut self) -> Result<(), Box<dyn Error>> {
+	/// Return self with a persistent parse cache stored in the given file. Files whose contents did not change since the last run are not parsed again.
+	pub fn with_parse_cache(mut self, cache_file:&str) -> Self {
+		Arc::make_mut(&mut self.config).parse_cache = Some(Arc::new(Mutex::new(ParseCache::open(cache_file))));
+		self
+	}
+
+	/// Return self with an undo journal stored in the given directory. Every generation that rewrites files is recorded, so the last one can be undone.
+	pub fn with_undo_journal(mut self, journal_dir:&str) -> Self {
+		Arc::make_mut(&mut self.config).undo_journal = Some(UndoJournal::new(journal_dir));
+		self
+	}
+
+	/// Return self with additional patterns of files and directories to skip when scanning directories. Uses the same syntax as lines in a .gitignore file, relative to the directory of the root file.
+	pub fn with_ignore_patterns(mut self, patterns:&[&str]) -> Self {
+		let base_dir:String = Self::parent_dir(&self.file).map(|dir| dir.path().to_string()).unwrap_or_default();
+		let config:&mut UpdaterConfig = Arc::make_mut(&mut self.config);
+		config.ignore_patterns = config.ignore_patterns.clone().with_patterns(&base_dir, patterns);
+		self
+	}
+
+	/// Return self without respecting .gitignore files when scanning directories. Ignore files specific to this tool are still respected.
+	pub fn without_gitignore(mut self) -> Self {
+		Arc::make_mut(&mut self.config).skip_gitignore = true;
+		self
+	}
+
+	/// Return self with a way to scaffold module files for directories that contain Rust files but no module file. Scaffolded files only contain the auto-exports tag and are created when writing.
+	pub fn with_module_scaffolding(mut self, module_scaffolding:ModuleScaffolding) -> Self {
+		Arc::make_mut(&mut self.config).module_scaffolding = module_scaffolding;
+		self
+	}
+
+	/// Return self with a different suffix for unit test files, the default is "_u". Test files are declared behind `#[cfg(test)]` and never re-exported. An empty suffix treats all files as regular modules.
+	pub fn with_test_file_suffix(mut self, suffix:&str) -> Self {
+		Arc::make_mut(&mut self.config).test_file_suffix = Some(suffix.to_string());
+		self
+	}
 
-		// If already parsed, don't parse again.
-		if self.parsed {
-			return Ok(());
+	/// Return self with a different mapping of module name suffixes to cfg conditions. By default, modules ending in "_linux", "_windows" or "_macos" are declared behind the matching `target_os` condition. An empty mapping disables suffix gating.
+	pub fn with_cfg_suffixes(mut self, cfg_suffixes:&[(&str, &str)]) -> Self {
+		Arc::make_mut(&mut self.config).cfg_suffixes = Some(cfg_suffixes.iter().map(|(suffix, condition)| (suffix.to_string(), condition.to_string())).collect());
+		self
+	}
+
+	/// Return self with a different name for feature directories, the default is "features". Modules directly inside a feature directory are declared behind the feature with their name. An empty name disables feature gating.
+	pub fn with_feature_dir(mut self, dir_name:&str) -> Self {
+		Arc::make_mut(&mut self.config).feature_dir = Some(dir_name.to_string());
+		self
+	}
+
+	/// Return self with modules ordered by name instead of longest name first. This is the order rustfmt uses for mod declarations and imports of snake_case modules, so the output is stable under rustfmt, but existing auto-exports are reordered on the next generation.
+	pub fn with_sorted_modules(mut self) -> Self {
+		Arc::make_mut(&mut self.config).sorted_modules = true;
+		self
+	}
+
+	/// Return self with a specific amount of threads to parse files with. Using a single thread parses all files sequentially, the generated output does not depend on the amount of threads.
+	pub fn with_threads(mut self, threads:usize) -> Self {
+		Arc::make_mut(&mut self.config).threads = threads.max(1);
+		self
+	}
+
+
+	/* USAGE METHODS */
+
+	/// Find all imports and exports for this file and all linked files, and generate auto-exports where the tag is present.
+	pub fn generate(&mut self) -> Result<(), Error> {
+		let transaction:FileTransaction = self.config.transaction();
+		self.generate_in_transaction(transaction)
+	}
+
+	/// Generate auto-exports and commit them in the given transaction, which may already contain committed steps. Rolls back the whole transaction on failure.
+	fn generate_in_transaction(&mut self, mut transaction:FileTransaction) -> Result<(), Error> {
+		let fingerprint:String = self.config.fingerprint();
+		if let Some(mut parse_cache) = self.lock_parse_cache() {
+			parse_cache.validate(&fingerprint);
+		}
+		let result:Result<(), Error> = match self.parse_tree().and_then(|_| self.write_tree(&mut transaction)) {
+			Ok(_) => transaction.commit(),
+			Err(error) => Err(match transaction.rollback() {
+				Ok(_) => error,
+				Err(rollback_error) => Error::rollback_failed(error, rollback_error)
+			})
+		};
+		if result.is_err() {
+			self.discard_staged();
+		}
+		if let Some(mut parse_cache) = self.lock_parse_cache() {
+			parse_cache.save()?;
+		}
+		result
+	}
+
+	/// Parse all files in the tree that were not parsed yet. Each round parses all currently known unparsed files concurrently, which discovers the next set of linked files.
+	fn parse_tree(&mut self) -> Result<(), Error> {
+		let thread_count:usize = self.config.thread_count();
+		loop {
+			let mut unparsed_finders:Vec<&mut ItemImportExportUpdater> = self.unparsed_finders();
+			if unparsed_finders.is_empty() {
+				return Ok(());
+			}
+
+			// Parse sequentially when only using one thread.
+			if thread_count == 1 || unparsed_finders.len() == 1 {
+				unparsed_finders.into_iter().try_for_each(|finder| finder.parse_file())?;
+				continue;
+			}
+
+			// Spread the files over threads.
+			let chunk_size:usize = unparsed_finders.len().div_ceil(thread_count);
+			let results:Vec<Result<(), Error>> = thread::scope(|scope| {
+				let handles:Vec<ScopedJoinHandle<Result<(), Error>>> = unparsed_finders.chunks_mut(chunk_size).map(|chunk| scope.spawn(move || chunk.iter_mut().try_for_each(|finder| finder.parse_file()))).collect();
+				handles.into_iter().map(|handle| handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))).collect()
+			});
+			results.into_iter().collect::<Result<(), Error>>()?;
+		}
+	}
+
+	/// Get all finders in the tree that were not parsed yet. Does not look into the sub-finders of unparsed finders, as those will be discovered when parsing.
+	fn unparsed_finders(&mut self) -> Vec<&mut ItemImportExportUpdater> {
+		if !self.parsed {
+			vec![self]
+		} else {
+			self.sub_finders.iter_mut().flat_map(|sub_finder| sub_finder.unparsed_finders()).collect()
 		}
+	}
+
+	/// Parse this file and find the files linked to it. Does not parse the linked files.
+	fn parse_file(&mut self) -> Result<(), Error> {
 		self.parsed = true;
+		self.is_mod_file = Self::is_module_file(&self.file);
 
 		// Read and parse file.
-		let mut exports_trigger_location:Option<usize> = None;
-		self.imports = Vec::new();
+		self.diagnostics = Vec::new();
+		self.rewritten = false;
+		let file_contents:String = if self.scaffolded && !self.file.exists() { format!("// {AUTO_EXPORTS_TAG}\n") } else { self.file.read().map_err(|error| Error::io(self.file.path(), error))? };
+		let parsed_file:ParsedFile = self.parse_contents(&file_contents)?;
+		self.imports = parsed_file.imports;
 		self.exports.iter_mut().for_each(|(_, list)| *list = Vec::new());
-		let file_contents:String = self.file.read()?;
-		for (match_cursor, match_result) in imports_exports_parser().find_matches(&file_contents) {
-
-			// Imports and exports.
-			if match_result.type_name == MODULE_IMPORT_TAG || match_result.type_name == PARSER_EXPORT_TAG {
-				let pub_type:Option<PubType> = match_result.find_child_by_type_path(&[PARSER_PUB_TYPE_TAG]).map(|child| PubType::from_str(&child.contents));
-				let struct_type:String = match_result.find_child_by_type_path(&[PARSER_TYPE_TAG]).unwrap().contents.clone();
-				let identifier:String = match_result.find_child_by_type_path(&[PARSER_IDENTIFIER_TAG]).unwrap().contents.clone();
+		for export in parsed_file.exports {
+			if let Some((_, list)) = self.exports.iter_mut().find(|(list_pub_type, _)| Some(list_pub_type) == export.pub_type.as_ref()) {
+				list.push(export);
+			}
+		}
+		self.auto_exports_source = parsed_file.auto_exports_trigger.map(|cursor| (file_contents, cursor));
 
-				if match_result.type_name == MODULE_IMPORT_TAG {
-					self.imports.push(Import { pub_type: pub_type.clone(), struct_type: struct_type.clone(), identifier: identifier.clone() });
+		// Parse other linked files. Sub-finders that were already parsed before are kept as they are.
+		let mut previous_sub_finders:Vec<ItemImportExportUpdater> = std::mem::take(&mut self.sub_finders);
+		let module_dir:FileRef = Self::module_dir(&self.file)?;
+		for import in self.imports.iter().filter(|import| import.struct_type == "mod" && !import.identifier.contains('{')) {
+			let next_file_refix:FileRef = module_dir.clone() + "/" + &import.identifier;
+			let next_files:Vec<FileRef> = [next_file_refix.clone() + ".rs", next_file_refix.clone() + "/mod.rs"].into_iter().filter(|file| file.exists()).collect();
+			if next_files.is_empty() {
+				self.diagnostics.push(
+					Diagnostic::warning(DIAGNOSTIC_UNRESOLVED_MODULE, &format!("could not find the file for module `{}`", import.identifier), self.file.path())
+						.with_span(import.span.clone())
+						.with_label("module declared here")
+						.with_note(&format!("expected `{}.rs` or `{}/mod.rs`", import.identifier, import.identifier))
+				);
+			}
+			for next_file in next_files {
+				self.sub_finders.push(Self::take_sub_finder(&mut previous_sub_finders, &next_file, &self.config));
+			}
+		}
+		if self.is_mod_file && module_dir.exists() {
+			let ignore_patterns:IgnorePatterns = self.config.ignore_patterns(module_dir.path());
+			for file in module_dir.scanner().include_files().filter(|file| file.name() != "mod.rs" && file.name() != "lib.rs" && file.extension() == Some("rs") && !ignore_patterns.is_ignored(file.path(), false)) {
+				if self.sub_finders.iter().find(|sub_finder| sub_finder.file == file).is_none() {
+					self.sub_finders.push(Self::take_sub_finder(&mut previous_sub_finders, &file, &self.config));
 				}
-				if match_result.type_name == PARSER_EXPORT_TAG {
-					if let Some(pub_type) = pub_type {
-						if let Some((_, list)) = self.exports.iter_mut().find(|(list_pub_type, _)| *list_pub_type == pub_type) {
-							list.push(Export { pub_type: Some(pub_type), struct_type, identifier });
-						}
+			}
+			for dir in module_dir.list_dirs().into_iter().filter(|dir| !ignore_patterns.is_ignored(dir.path(), true)) {
+				let file:FileRef = dir.clone() + "/mod.rs";
+				if file.exists() {
+					if self.sub_finders.iter().find(|sub_finder| sub_finder.file == file).is_none() {
+						self.sub_finders.push(Self::take_sub_finder(&mut previous_sub_finders, &file, &self.config));
 					}
+				} else if let Some(file) = self.scaffold_file(&dir, &ignore_patterns) {
+					let mut sub_finder:ItemImportExportUpdater = Self::take_sub_finder(&mut previous_sub_finders, &file, &self.config);
+					sub_finder.scaffolded = true;
+					self.sub_finders.push(sub_finder);
 				}
 			}
+		}
 
-			// Auto-exporting trigger.
-			if match_result.type_name == PARSER_AUTO_EXPORTS_TRIGGER_TAG {
-				exports_trigger_location = Some(match_cursor);
-			}
+		// Return success.
+		Ok(())
+	}
+
+	/// Generate the auto-exports of all files parsed since the last write, deepest files first. The new contents are staged in the transaction, nothing is written until it is committed.
+	fn write_tree(&mut self, transaction:&mut FileTransaction) -> Result<(), Error> {
+		for sub_finder in &mut self.sub_finders {
+			sub_finder.write_tree(transaction)?;
+		}
+		if let Some((file_contents, cursor)) = self.auto_exports_source.take() {
+			self.generate_auto_exports(&file_contents, cursor, transaction)?;
+			self.find_export_collisions();
 		}
+		Ok(())
+	}
 
-		// Parse other linked files.
-		self.sub_finders = Vec::new();
-		for import in self.imports.iter().filter(|import| import.struct_type == "mod") {
-			let next_file_refix:FileRef = self.file.parent_dir()? + "/" + &import.identifier;
-			for next_file in [next_file_refix.clone() + ".rs", next_file_refix.clone() + "/mod.rs"] {
-				if next_file.exists() {
-					self.sub_finders.push(ItemImportExportUpdater::new(next_file.path()));
-				}
+	/// Mark all finders with staged contents as not rewritten and not parsed, so the next generation stages them again after a failed commit.
+	fn discard_staged(&mut self) {
+		if self.rewritten {
+			self.rewritten = false;
+			self.parsed = false;
+		}
+		self.sub_finders.iter_mut().for_each(|sub_finder| sub_finder.discard_staged());
+	}
+
+	/// Convert all modules in the tree to the given layout, moving `foo/mod.rs` to `foo.rs` or the other way around. Modules without a directory and the crate root are not moved. Regenerates auto-exports afterwards. Returns the moved files as pairs of old and new paths.
+	pub fn convert_layout(&mut self, layout:ModuleLayout) -> Result<Vec<(String, String)>, Error> {
+		self.parse_tree()?;
+		let moves:Vec<(FileRef, FileRef)> = self.layout_moves(layout, None)?;
+		self.move_module_files(moves)
+	}
+
+	/// Convert a single module to the given layout, moving `foo/mod.rs` to `foo.rs` or the other way around. The module can be given by either of its possible file paths. Sub-modules are not converted. Returns the moved files as pairs of old and new paths.
+	pub fn convert_module_layout(&mut self, module_file:&str, layout:ModuleLayout) -> Result<Vec<(String, String)>, Error> {
+		self.parse_tree()?;
+		let module_file:FileRef = FileRef::new(module_file).absolute();
+		let moves:Vec<(FileRef, FileRef)> = self.layout_moves(layout, Some(&module_file))?;
+		if moves.is_empty() && !self.contains_file(&module_file) {
+			return Err(Error::resolution(module_file.path(), None, "module file is not part of the crate"));
+		}
+		self.move_module_files(moves)
+	}
+
+	/// Move module files, then rebuild the tree and regenerate auto-exports in the same transaction, so both are undone together. Module directories left empty by the move are removed.
+	fn move_module_files(&mut self, moves:Vec<(FileRef, FileRef)>) -> Result<Vec<(String, String)>, Error> {
+		let mut transaction:FileTransaction = self.config.transaction();
+		for (source, target) in &moves {
+			if target.exists() {
+				return Err(Error::resolution(source.path(), None, &format!("cannot move module file, {} already exists", target.path())));
 			}
+			let contents:String = source.read().map_err(|error| Error::io(source.path(), error))?;
+			transaction.stage(target.path(), contents.clone(), None);
+			transaction.stage_removal(source.path(), contents);
 		}
-		if self.is_mod_file {
-			for file in self.file.parent_dir()?.scanner().include_files().filter(|file| file.name() != "mod.rs" && file.name() != "lib.rs" && file.extension() == Some("rs")) {
-				if self.sub_finders.iter().find(|sub_finder| sub_finder.file == file).is_none() {
-					self.sub_finders.push(ItemImportExportUpdater::new(file.path()));
-				}
+		if !moves.is_empty() {
+			transaction.commit_step()?;
+			if let Err(error) = moves.iter().try_for_each(|(source, _)| remove_empty_dir(&Self::parent_dir(source)?)) {
+				return Err(match transaction.rollback() {
+					Ok(_) => error,
+					Err(rollback_error) => Error::rollback_failed(error, rollback_error)
+				});
 			}
-			for file in self.file.parent_dir()?.list_dirs().into_iter().map(|dir| dir + "/mod.rs").filter(|file| file.exists()) {
-				if self.sub_finders.iter().find(|sub_finder| sub_finder.file == file).is_none() {
-					self.sub_finders.push(ItemImportExportUpdater::new(file.path()));
-				}
+			self.sub_finders = Vec::new();
+			self.parsed = false;
+			self.generate_in_transaction(transaction)?;
+		}
+		Ok(moves.into_iter().map(|(source, target)| (source.path().to_string(), target.path().to_string())).collect())
+	}
+
+	/// Get the moves required to convert the modules in the tree to the given layout. Only includes the given module file if one is given.
+	fn layout_moves(&self, layout:ModuleLayout, only_file:Option<&FileRef>) -> Result<Vec<(FileRef, FileRef)>, Error> {
+		let mut moves:Vec<(FileRef, FileRef)> = Vec::new();
+		let is_selected:bool = match only_file {
+			Some(only_file) => &self.file == only_file || self.layout_target(ModuleLayout::ModFile)?.as_ref() == Some(only_file) || self.layout_target(ModuleLayout::SiblingFile)?.as_ref() == Some(only_file),
+			None => true
+		};
+		if is_selected {
+			if let Some(target) = self.layout_target(layout)? {
+				moves.push((self.file.clone(), target));
 			}
 		}
+		for sub_finder in &self.sub_finders {
+			moves.extend(sub_finder.layout_moves(layout, only_file)?);
+		}
+		Ok(moves)
+	}
+
+	/// Get the path this file should be moved to for the given layout. Returns None if the file already uses the layout, is a crate root or is a module without a directory.
+	fn layout_target(&self, layout:ModuleLayout) -> Result<Option<FileRef>, Error> {
+		match (self.file.name(), layout) {
+			("lib.rs" | "main.rs", _) | ("mod.rs", ModuleLayout::ModFile) => Ok(None),
+			("mod.rs", ModuleLayout::SiblingFile) => Ok(Some(FileRef::new(&(Self::parent_dir(&self.file)?.path().trim_end_matches('/').to_string() + ".rs")))),
+			(_, ModuleLayout::ModFile) => {
+				let module_dir:FileRef = Self::module_dir(&self.file)?;
+				Ok(if module_dir.exists() { Some(module_dir + "/mod.rs") } else { None })
+			},
+			(_, ModuleLayout::SiblingFile) => Ok(None)
+		}
+	}
+
+	/// Whether or not this finder or any of its sub-finders handles the given file.
+	fn contains_file(&self, file:&FileRef) -> bool {
+		&self.file == file || self.sub_finders.iter().any(|sub_finder| sub_finder.contains_file(file))
+	}
+
+	/// Undo the last recorded generation or layout conversion, restoring all files it touched. Requires an undo journal, configured with `with_undo_journal`, and refuses if any of the files was edited since. See the `undo` example for a command line entry point. Returns the paths of all restored files.
+	pub fn undo(&mut self) -> Result<Vec<String>, Error> {
+		let undo_journal:&UndoJournal = self.config.undo_journal.as_ref().ok_or_else(|| Error::undo(self.file.path(), "no undo journal configured"))?;
+		let restored_files:Vec<String> = undo_journal.undo()?;
+		self.sub_finders = Vec::new();
+		self.parsed = false;
+		Ok(restored_files)
+	}
+
+	/// Re-generate only the files affected by the given changed paths, keeping the parsed state of all other files. Returns the paths of all rewritten files.
+	pub fn regenerate(&mut self, changed_paths:&[String]) -> Result<Vec<String>, Error> {
+		self.clear_rewritten();
+		for changed_path in changed_paths {
+			self.invalidate(changed_path);
+		}
+		self.generate()?;
+		Ok(self.rewritten_files().into_iter().map(|path| path.to_string()).collect())
+	}
+
+	/// Re-generate all files, parsing the whole tree again. Used when it is unknown which files changed. Returns the paths of all rewritten files.
+	pub fn regenerate_all(&mut self) -> Result<Vec<String>, Error> {
+		self.clear_rewritten();
+		self.sub_finders = Vec::new();
+		self.parsed = false;
+		self.generate()?;
+		Ok(self.rewritten_files().into_iter().map(|path| path.to_string()).collect())
+	}
+
+	/// Mark this finder and all sub-finders affected by a changed path as not parsed, so the next generation parses them again. A mod file is affected by any change inside its directory. Returns whether this finder was affected.
+	fn invalidate(&mut self, changed_path:&str) -> bool {
+		let changed_path:String = changed_path.replace('\\', "/");
+		let file_path:String = self.file.path().replace('\\', "/");
+		let module_dir:&str = match self.file.name() {
+			"lib.rs" | "main.rs" | "mod.rs" => &file_path[..file_path.rfind('/').unwrap_or(0)],
+			_ => file_path.trim_end_matches(".rs")
+		};
+		let mut affected:bool = file_path == changed_path || (self.is_mod_file && changed_path.starts_with(&format!("{module_dir}/")));
 		for sub_finder in &mut self.sub_finders {
-			sub_finder.generate()?;
+			affected |= sub_finder.invalidate(&changed_path);
+		}
+		if affected {
+			self.parsed = false;
 		}
+		affected
+	}
+
+	/// Reset the rewritten state of this finder and all sub-finders.
+	fn clear_rewritten(&mut self) {
+		self.rewritten = false;
+		self.sub_finders.iter_mut().for_each(|sub_finder| sub_finder.clear_rewritten());
+	}
 
-		// Handle auto-exports if tag present.
-		if let Some(cursor) = exports_trigger_location {
-			self.generate_auto_exports(&file_contents, cursor)?;
+	/// Get the path of the file this updater generates auto-exports for.
+	pub fn file(&self) -> &str {
+		self.file.path()
+	}
+
+	/// Get the file name suffix of unit test files. Other tools working on the same crate, like the library usage scanner, should use this suffix to agree on which files are test files.
+	pub fn test_file_suffix(&self) -> &str {
+		self.config.test_file_suffix()
+	}
+
+	/// Get the paths of all files rewritten by this finder and all sub-finders.
+	pub fn rewritten_files(&self) -> Vec<&str> {
+		[
+			if self.rewritten { vec![self.file.path()] } else { Vec::new() },
+			self.sub_finders.iter().flat_map(|finder| finder.rewritten_files()).collect::<Vec<&str>>()
+		].into_iter().flatten().collect()
+	}
+
+	/// Take the sub-finder for a file from a list of previous sub-finders, or create a new one if it does not exist.
+	fn take_sub_finder(previous_sub_finders:&mut Vec<ItemImportExportUpdater>, file:&FileRef, config:&Arc<UpdaterConfig>) -> ItemImportExportUpdater {
+		match previous_sub_finders.iter().position(|sub_finder| &sub_finder.file == file) {
+			Some(index) => previous_sub_finders.remove(index),
+			None => ItemImportExportUpdater::with_config(file.path(), config.clone())
 		}
+	}
 
-		// Return success.
-		Ok(())
+	/// Parse the contents of this file. Uses the parse cache if available.
+	fn parse_contents(&self, contents:&str) -> Result<ParsedFile, Error> {
+		let hash:u64 = content_hash(contents);
+		if let Some(parsed_file) = self.lock_parse_cache().and_then(|parse_cache| parse_cache.get(self.file.path(), hash).cloned()) {
+			return Ok(parsed_file);
+		}
+		let parsed_file:ParsedFile = parse_file_contents(self.file.path(), contents)?;
+		if let Some(mut parse_cache) = self.lock_parse_cache() {
+			parse_cache.insert(self.file.path(), hash, parsed_file.clone());
+		}
+		Ok(parsed_file)
+	}
+
+	/// Lock the parse cache, if there is one.
+	fn lock_parse_cache(&self) -> Option<MutexGuard<'_, ParseCache>> {
+		self.config.parse_cache.as_ref().map(|parse_cache| parse_cache.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+	}
+
+	/// Get the module file to scaffold for a directory without a mod.rs file. Returns None if scaffolding is disabled, the directory contains no Rust files or already has a sibling module file.
+	fn scaffold_file(&self, dir:&FileRef, ignore_patterns:&IgnorePatterns) -> Option<FileRef> {
+		let sibling_file:FileRef = FileRef::new(&(dir.path().trim_end_matches('/').to_string() + ".rs"));
+		if sibling_file.exists() || !contains_rust_files(Path::new(dir.path()), ignore_patterns) {
+			return None;
+		}
+		match self.config.module_scaffolding {
+			ModuleScaffolding::Disabled => None,
+			ModuleScaffolding::ModFile => Some(dir.clone() + "/mod.rs"),
+			ModuleScaffolding::SiblingFile => Some(sibling_file)
+		}
+	}
+
+	/// Whether or not a file is a module file that owns a directory of sub-modules. This is the case for lib.rs, main.rs and mod.rs files, and for files with a directory of the same name next to them.
+	fn is_module_file(file:&FileRef) -> bool {
+		matches!(file.name(), "lib.rs" | "main.rs" | "mod.rs") || Path::new(file.path().trim_end_matches(".rs")).is_dir()
+	}
+
+	/// Get the directory containing the sub-modules of a file. This is the parent directory for lib.rs, main.rs and mod.rs files, and the directory with the same name as the file otherwise.
+	fn module_dir(file:&FileRef) -> Result<FileRef, Error> {
+		match file.name() {
+			"lib.rs" | "main.rs" | "mod.rs" => Self::parent_dir(file),
+			_ => Ok(Self::parent_dir(file)? + "/" + file.file_name_no_extension())
+		}
+	}
+
+	/// Get the parent directory of a file.
+	fn parent_dir(file:&FileRef) -> Result<FileRef, Error> {
+		file.parent_dir().map_err(|error| Error::resolution(file.path(), None, &format!("could not find parent directory: {error}")))
 	}
 
-	/// Get all exports of this finder and all sub-finders.
+	/// Get all exports of this finder and all sub-finders. Test files do not export anything.
 	fn recursive_exports(&self) -> Vec<&(PubType, Vec<Export>)> {
+		if self.is_test_file {
+			return Vec::new();
+		}
 		[
 			self.exports.iter().collect::<Vec<&(PubType, Vec<Export>)>>(),
 			self.sub_finders.iter().map(|finder| finder.recursive_exports()).flatten().collect::<Vec<&(PubType, Vec<Export>)>>()
 		].into_iter().flatten().collect()
 	}
 
-	/// Get all imports of this finder and all sub-finders.
-	pub(crate) fn recursive_imports(&self) -> Vec<&Import> {
+	/// Get all exports of this finder and all sub-finders that are not test files, paired with the path of the file that defines them.
+	fn recursive_export_sources(&self) -> Vec<(&str, &Export)> {
+		if self.is_test_file {
+			return Vec::new();
+		}
+		[
+			self.exports.iter().flat_map(|(_, list)| list.iter().map(|export| (self.file.path(), export))).collect::<Vec<(&str, &Export)>>(),
+			self.sub_finders.iter().flat_map(|finder| finder.recursive_export_sources()).collect::<Vec<(&str, &Export)>>()
+		].into_iter().flatten().collect()
+	}
+
+	/// Get all diagnostics of this finder and all sub-finders.
+	pub fn diagnostics(&self) -> Vec<&Diagnostic> {
 		[
-			self.imports.iter().collect::<Vec<&Import>>(),
-			self.sub_finders.iter().map(|finder| finder.recursive_imports()).flatten().collect::<Vec<&Import>>()
+			self.diagnostics.iter().collect::<Vec<&Diagnostic>>(),
+			self.sub_finders.iter().flat_map(|finder| finder.diagnostics()).collect::<Vec<&Diagnostic>>()
 		].into_iter().flatten().collect()
 	}
 
 
 
-	/// Generate auto-exports for this file. Does nothing if the file does not contain the auto-exports tag.
-	fn generate_auto_exports(&self, file_contents:&str, exports_trigger_location:usize) -> Result<(), Box<dyn Error>> {
+	/// Generate auto-exports for this file and stage the new contents in the transaction. Does nothing if the file does not contain the auto-exports tag.
+	fn generate_auto_exports(&mut self, file_contents:&str, exports_trigger_location:usize, transaction:&mut FileTransaction) -> Result<(), Error> {
 
 		// Collect exports by mod_name, then pub type, then items.
 		let mut item_exports:Vec<(String, [(PubType, Vec<Export>); 3])> = Vec::new();
+		let mut mod_conditions:Vec<(String, String)> = Vec::new();
 		for sub_finder in &self.sub_finders {
 			let file_name:&str = sub_finder.file.file_name_no_extension();
-			let mod_name:String = if file_name == "mod" || file_name == "lib" { sub_finder.file.parent_dir()?.file_name_no_extension().to_string() } else { sub_finder.file.file_name_no_extension().to_string() };
+			let mod_name:String = if file_name == "mod" || file_name == "lib" { Self::parent_dir(&sub_finder.file)?.file_name_no_extension().to_string() } else { sub_finder.file.file_name_no_extension().to_string() };
+			if let Some(cfg_condition) = &sub_finder.cfg_condition {
+				mod_conditions.push((mod_name.clone(), cfg_condition.clone()));
+			}
 			let list_index:usize = match item_exports.iter().position(|(list_mod_name, _)| list_mod_name == &mod_name) {
 				Some(index) => index,
 				None => {
@@ -142,36 +624,93 @@ impl ItemImportExportUpdater {
 			}
 		}
 
-		// Sort items by length of name.
-		item_exports.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
-
-		// Generate and store new contents.
-		let new_contents:String = format!(
-			"{}// {}\n{}\n\n{}",
-			&file_contents[..exports_trigger_location],
-			AUTO_EXPORTS_TAG,
-			item_exports
-				.iter()
-				.map(|(mod_name, _)| format!("mod {mod_name};"))
-				.collect::<Vec<String>>()
-				.join("\n"),
-			item_exports
-				.iter()
-				.map(|(mod_name, pub_typed_items)|
-					pub_typed_items
-						.iter()
-						.filter(|(pub_type, items)| !items.is_empty() && !(pub_type == &PubType::Super && self.is_mod_file))
-						.map(|(pub_type, items)| format!("{} use {}::*; // {}", pub_type.to_str(), mod_name, items.into_iter().map(|export| export.identifier.clone()).collect::<Vec<String>>().join(", ")))
-						.collect::<Vec<String>>()
-						.join("\n")
-				)
-				.collect::<Vec<String>>()
-				.join("\n"),
-		);
-		if new_contents != file_contents {
-			self.file.write(new_contents)?;
+		// Sort modules by length of name, or by name when configured.
+		if self.config.sorted_modules {
+			item_exports.sort_by(|a, b| a.0.cmp(&b.0));
+		} else {
+			item_exports.sort_by_key(|(mod_name, _)| Reverse(mod_name.len()));
+		}
+
+		// Detect the formatting of the file.
+		let line_ending:&str = if file_contents.contains("\r\n") { "\r\n" } else { "\n" };
+		let line_start:usize = file_contents[..exports_trigger_location].rfind('\n').map(|index| index + 1).unwrap_or(0);
+		let indentation:&str = Some(&file_contents[line_start..exports_trigger_location]).filter(|indentation| indentation.trim().is_empty()).unwrap_or_default();
+		let final_line_ending:&str = if file_contents.ends_with('\n') { line_ending } else { "" };
+
+		// Generate lines.
+		let cfg_attribute = |mod_name:&str| mod_conditions.iter().find(|(condition_mod_name, _)| condition_mod_name == mod_name).map(|(_, condition)| format!("{indentation}#[cfg({condition})]"));
+		let mut lines:Vec<String> = vec![format!("// {AUTO_EXPORTS_TAG}")];
+		for (mod_name, _) in &item_exports {
+			lines.extend(cfg_attribute(mod_name));
+			lines.push(format!("{indentation}mod {mod_name};"));
+		}
+		let mut use_lines:Vec<String> = Vec::new();
+		for (mod_name, pub_typed_items) in &item_exports {
+			for (pub_type, items) in pub_typed_items.iter().filter(|(pub_type, items)| !items.is_empty() && !(pub_type == &PubType::Super && self.is_mod_file)) {
+				use_lines.extend(cfg_attribute(mod_name));
+				use_lines.push(format!("{indentation}{} use {}::*; // {}", pub_type.to_str(), mod_name, items.iter().map(|export| export.identifier.clone()).collect::<Vec<String>>().join(", ")));
+			}
+		}
+		if !use_lines.is_empty() {
+			lines.push(String::new());
+			lines.extend(use_lines);
+		}
+
+		// Store new contents.
+		let new_contents:String = format!("{}{}{}", &file_contents[..exports_trigger_location], lines.join(line_ending), final_line_ending);
+		if new_contents != file_contents || !self.file.exists() {
+			let original_contents:Option<String> = if self.file.exists() { Some(file_contents.to_string()) } else { None };
+			transaction.stage(self.file.path(), new_contents, original_contents);
+			self.rewritten = true;
 		}
 
 		Ok(())
 	}
+
+	/// Find items with the same name that are glob-exported from different modules into this file. Modules behind different cfg conditions, like platform-specific implementations, may export the same names.
+	fn find_export_collisions(&mut self) {
+		let mut seen:Vec<(String, &Option<String>, &str, &Export)> = Vec::new();
+		let mut collisions:Vec<Diagnostic> = Vec::new();
+		for sub_finder in &self.sub_finders {
+			for (file, export) in sub_finder.recursive_export_sources() {
+				if export.pub_type == Some(PubType::Super) && self.is_mod_file {
+					continue;
+				}
+				let is_exclusive = |seen_condition:&Option<String>| seen_condition.is_some() && sub_finder.cfg_condition.is_some() && seen_condition != &sub_finder.cfg_condition;
+				match seen.iter().find(|(seen_file, seen_condition, _, seen_export)| *seen_file != sub_finder.file.path() && seen_export.identifier == export.identifier && !is_exclusive(seen_condition)) {
+					Some((_, _, original_file, original_export)) => collisions.push(
+						Diagnostic::warning(DIAGNOSTIC_EXPORT_COLLISION, &format!("`{}` is exported by multiple modules re-exported into {}", export.identifier, self.file.path()), file)
+							.with_span(export.span.clone())
+							.with_label("conflicting export")
+							.with_note(&format!("also exported at {}:{}:{}", original_file, original_export.span.line, original_export.span.column))
+					),
+					None => seen.push((sub_finder.file.path().to_string(), &sub_finder.cfg_condition, file, export))
+				}
+			}
+		}
+		self.diagnostics.extend(collisions);
+	}
+}
+
+
+
+/// Whether or not a file only contains unit tests, based on the test file suffix of its name. Crate roots and mod.rs files are never test files, an empty suffix disables test files.
+pub(crate) fn is_test_file(file:&FileRef, suffix:&str) -> bool {
+	!suffix.is_empty() && !matches!(file.name(), "lib.rs" | "main.rs" | "mod.rs") && file.file_name_no_extension().ends_with(suffix)
+}
+
+/// Whether or not a directory contains any Rust files that are not ignored, directly or in any of its sub-directories.
+fn contains_rust_files(dir:&Path, ignore_patterns:&IgnorePatterns) -> bool {
+	std::fs::read_dir(dir).into_iter().flatten().flatten().map(|entry| entry.path()).any(|path| {
+		let is_dir:bool = path.is_dir();
+		!ignore_patterns.is_ignored(&path.to_string_lossy(), is_dir) && if is_dir { contains_rust_files(&path, ignore_patterns) } else { path.extension().is_some_and(|extension| extension == "rs") }
+	})
+}
+
+/// Remove a directory if it is empty. Does nothing if it contains any files or directories.
+fn remove_empty_dir(dir:&FileRef) -> Result<(), Error> {
+	if std::fs::read_dir(dir.path()).is_ok_and(|mut entries| entries.next().is_none()) {
+		std::fs::remove_dir(dir.path()).map_err(|error| Error::io(dir.path(), error))?;
+	}
+	Ok(())
 }
\ No newline at end of file
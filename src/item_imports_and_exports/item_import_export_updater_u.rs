@@ -0,0 +1,317 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ Error, ItemImportExportUpdater, ModuleLayout, ModuleScaffolding };
+	use std::{ io::Write, path::Path, process::{ Command, Output, Stdio }, time::{ Duration, Instant } };
+
+
+	/// Create a synthetic crate in the given directory, removing any previous contents. Returns the path to its lib file.
+	fn create_test_crate(dir:&str, module_count:usize, files_per_module:usize, lines_per_function:usize) -> String {
+		let _ = std::fs::remove_dir_all(dir);
+		for module_index in 0..module_count {
+			let module_dir:String = format!("{dir}/module_{module_index}");
+			std::fs::create_dir_all(&module_dir).unwrap();
+			std::fs::write(format!("{module_dir}/mod.rs"), "// auto-exports\n").unwrap();
+			for file_index in 0..files_per_module {
+				let function_body:String = (0..lines_per_function).map(|line_index| format!("\tlet value_{line_index}:&str = \"{{ {line_index} }}\"; // use {line_index} {{\n")).collect();
+				let contents:String = format!(
+					"use std::fmt::Display;\n\npub struct Item{module_index}x{file_index} {{\n\tvalue:u32\n}}\n\npub(crate) fn function_{module_index}_{file_index}() {{\n{function_body}}}\n\n/* pub fn hidden() {{}} */\npub const CONSTANT_{module_index}_{file_index}:&str = \"mod fake;\";\n"
+				);
+				std::fs::write(format!("{module_dir}/file_{file_index}.rs"), contents).unwrap();
+			}
+		}
+		std::fs::write(format!("{dir}/lib.rs"), "// auto-exports\n").unwrap();
+		format!("{dir}/lib.rs")
+	}
+
+	/// Read all files in a directory recursively, sorted by path.
+	fn read_files_recursive(dir:&Path) -> Vec<(String, String)> {
+		let mut files:Vec<(String, String)> = Vec::new();
+		for entry in std::fs::read_dir(dir).unwrap().flatten() {
+			let path = entry.path();
+			if path.is_dir() {
+				files.extend(read_files_recursive(&path));
+			} else {
+				files.push((path.to_string_lossy().to_string(), std::fs::read_to_string(&path).unwrap()));
+			}
+		}
+		files.sort();
+		files
+	}
+
+
+	#[test]
+	fn test_parallel_output_matches_sequential() {
+		const CRATE_DIR:&str = "target/crate_manager/test_parallel_crate";
+
+		// Generate using a single thread.
+		let lib_file:String = create_test_crate(CRATE_DIR, 12, 8, 20);
+		ItemImportExportUpdater::new(&lib_file).with_threads(1).generate().unwrap();
+		let sequential_output:Vec<(String, String)> = read_files_recursive(Path::new(CRATE_DIR));
+
+		// Generate using multiple threads.
+		let lib_file:String = create_test_crate(CRATE_DIR, 12, 8, 20);
+		ItemImportExportUpdater::new(&lib_file).with_threads(8).generate().unwrap();
+		let parallel_output:Vec<(String, String)> = read_files_recursive(Path::new(CRATE_DIR));
+
+		// Compare outputs.
+		assert!(sequential_output.iter().any(|(path, contents)| path.ends_with("lib.rs") && contents.contains("pub use module_0::*;")));
+		assert_eq!(sequential_output, parallel_output);
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_enums_are_exported() {
+		const CRATE_DIR:&str = "target/crate_manager/test_enum_crate";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(CRATE_DIR).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/modes.rs"), "pub enum Mode { Read, Write }\nenum Hidden { A }\npub(crate) enum Internal { B }\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/private.rs"), "enum Private { C }\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+
+		// Public enums are exported like structs, private enums are not.
+		ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).generate().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\nmod private;\nmod modes;\n\npub use modes::*; // Mode\npub(crate) use modes::*; // Internal\n");
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_config_change_invalidates_parse_cache() {
+		const CRATE_DIR:&str = "target/crate_manager/test_cache_config_crate";
+		const CACHE_FILE:&str = "target/crate_manager/test_cache_config_crate/parse_cache.txt";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(CRATE_DIR).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/data.rs"), "pub struct Data;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+		ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_parse_cache(CACHE_FILE).generate().unwrap();
+
+		// Tamper with the cached exports, which are used as long as the configuration does not change.
+		let cache_contents:String = std::fs::read_to_string(CACHE_FILE).unwrap();
+		std::fs::write(CACHE_FILE, cache_contents.replace("\tData\t", "\tCached\t")).unwrap();
+		ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_parse_cache(CACHE_FILE).generate().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\nmod data;\n\npub use data::*; // Cached\n");
+
+		// Changing the configuration parses all files again.
+		ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_parse_cache(CACHE_FILE).with_test_file_suffix("_test").generate().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\nmod data;\n\npub use data::*; // Data\n");
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_generation_scales_linearly() {
+		const CRATE_DIR:&str = "target/crate_manager/test_scaling_crate";
+		const MAX_SCALING_FACTOR:f64 = 2.5;
+		const RUN_COUNT:usize = 3;
+
+		// Time generation for a crate of the given size, using the fastest of a few runs to reduce noise.
+		let generation_duration = |files_per_module:usize| -> Duration {
+			(0..RUN_COUNT).map(|_| {
+				let lib_file:String = create_test_crate(CRATE_DIR, 10, files_per_module, 150);
+				let start:Instant = Instant::now();
+				ItemImportExportUpdater::new(&lib_file).with_threads(1).generate().unwrap();
+				start.elapsed()
+			}).min().unwrap()
+		};
+
+		// Doubling the number of lines should roughly double the duration, independent of the speed of the machine.
+		let duration:Duration = generation_duration(10);
+		let double_duration:Duration = generation_duration(20);
+		assert!(
+			double_duration.as_secs_f64() <= duration.as_secs_f64() * MAX_SCALING_FACTOR,
+			"generating auto-exports for twice the lines took {double_duration:?}, expected at most {MAX_SCALING_FACTOR} times {duration:?}"
+		);
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_formatting_is_preserved() {
+		const CRATE_DIR:&str = "target/crate_manager/test_formatting_crate";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(CRATE_DIR).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/b.rs"), "pub fn function_b() {}\r\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/a.rs"), "pub(crate) struct A;\r\n").unwrap();
+
+		// Line endings and the final newline are preserved.
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "//! Crate.\r\n\r\n// auto-exports\r\n").unwrap();
+		let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs"));
+		updater.generate().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "//! Crate.\r\n\r\n// auto-exports\r\nmod a;\r\nmod b;\r\n\r\npub(crate) use a::*; // A\r\npub use b::*; // function_b\r\n");
+
+		// Indentation is preserved, blank lines are not indented.
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "\t// auto-exports").unwrap();
+		let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs"));
+		updater.generate().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "\t// auto-exports\n\tmod a;\n\tmod b;\n\n\tpub(crate) use a::*; // A\n\tpub use b::*; // function_b");
+
+		// Generating again does not change anything.
+		let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs"));
+		updater.generate().unwrap();
+		assert!(updater.rewritten_files().is_empty());
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_output_is_rustfmt_stable() {
+		const CRATE_DIR:&str = "target/crate_manager/test_rustfmt_crate";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(format!("{CRATE_DIR}/nested")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/beta.rs"), "pub fn function_b() {}\npub(crate) struct Internal;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/alpha.rs"), "pub struct Alpha;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/alpha_u.rs"), "fn test_alpha() {}\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/clipboard_linux.rs"), "pub struct Clipboard;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/clipboard_windows.rs"), "pub struct Clipboard;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/nested/mod.rs"), "// auto-exports\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/nested/inner.rs"), "pub(super) fn helper() {}\npub fn inner() {}\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+		ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_sorted_modules().generate().unwrap();
+
+		// With sorted modules, formatting the generated files does not change them. Skipped when rustfmt is not installed.
+		for file in ["lib.rs", "nested/mod.rs"] {
+			let contents:String = std::fs::read_to_string(format!("{CRATE_DIR}/{file}")).unwrap();
+			let Ok(mut rustfmt) = Command::new("rustfmt").args(["--edition", "2021", "--emit", "stdout"]).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn() else {
+				return;
+			};
+			rustfmt.stdin.take().unwrap().write_all(contents.as_bytes()).unwrap();
+			let output:Output = rustfmt.wait_with_output().unwrap();
+			assert!(output.status.success());
+			assert_eq!(String::from_utf8(output.stdout).unwrap(), contents);
+		}
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_module_scaffolding() {
+		const CRATE_DIR:&str = "target/crate_manager/test_scaffolding_crate";
+		for (module_scaffolding, module_file) in [(ModuleScaffolding::ModFile, "network/mod.rs"), (ModuleScaffolding::SiblingFile, "network.rs")] {
+			let _ = std::fs::remove_dir_all(CRATE_DIR);
+			std::fs::create_dir_all(format!("{CRATE_DIR}/network")).unwrap();
+			std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+			std::fs::write(format!("{CRATE_DIR}/network/client.rs"), "pub fn connect() {}\n").unwrap();
+
+			// Without scaffolding the directory is skipped.
+			ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).generate().unwrap();
+			assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\n");
+
+			// With scaffolding a module file is created.
+			ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_module_scaffolding(module_scaffolding).generate().unwrap();
+			assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/{module_file}")).unwrap(), "// auto-exports\nmod client;\n\npub use client::*; // connect\n");
+			assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\nmod network;\n\npub use network::*; // connect\n");
+
+			// The scaffolded file is used as a regular module file afterwards.
+			let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs"));
+			updater.generate().unwrap();
+			assert!(updater.rewritten_files().is_empty());
+		}
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_layout_conversion() {
+		const CRATE_DIR:&str = "target/crate_manager/test_layout_crate";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(format!("{CRATE_DIR}/network/protocol")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/network/mod.rs"), "// auto-exports\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/network/client.rs"), "pub fn connect() {}\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/network/protocol/mod.rs"), "// auto-exports\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/network/protocol/packet.rs"), "pub struct Packet;\n").unwrap();
+		std::fs::create_dir_all(format!("{CRATE_DIR}/settings")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/settings/mod.rs"), "pub struct Settings;\n").unwrap();
+		let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs"));
+		updater.generate().unwrap();
+
+		// Converting a module without sub-modules removes its empty directory.
+		assert_eq!(updater.convert_module_layout(&format!("{CRATE_DIR}/settings/mod.rs"), ModuleLayout::SiblingFile).unwrap().len(), 1);
+		assert!(Path::new(&format!("{CRATE_DIR}/settings.rs")).exists());
+		assert!(!Path::new(&format!("{CRATE_DIR}/settings")).exists());
+		let original_output:Vec<(String, String)> = read_files_recursive(Path::new(CRATE_DIR));
+
+		// Convert a single module.
+		assert_eq!(updater.convert_module_layout(&format!("{CRATE_DIR}/network/protocol/mod.rs"), ModuleLayout::SiblingFile).unwrap().len(), 1);
+		assert!(Path::new(&format!("{CRATE_DIR}/network/protocol.rs")).exists());
+		assert!(Path::new(&format!("{CRATE_DIR}/network/mod.rs")).exists());
+
+		// Convert the whole crate.
+		assert_eq!(updater.convert_layout(ModuleLayout::SiblingFile).unwrap().len(), 1);
+		assert!(!Path::new(&format!("{CRATE_DIR}/network/mod.rs")).exists());
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/network.rs")).unwrap(), "// auto-exports\nmod protocol;\nmod client;\n\npub use protocol::*; // Packet\npub use client::*; // connect\n");
+		assert!(Path::new(&format!("{CRATE_DIR}/network/protocol")).is_dir());
+		assert!(updater.rewritten_files().is_empty());
+
+		// Convert back.
+		assert_eq!(updater.convert_layout(ModuleLayout::ModFile).unwrap().len(), 2);
+		assert_eq!(read_files_recursive(Path::new(CRATE_DIR)), original_output);
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_undo_layout_conversion() {
+		const CRATE_DIR:&str = "target/crate_manager/test_undo_layout_crate";
+		const JOURNAL_DIR:&str = "target/crate_manager/test_undo_layout_journal";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(format!("{CRATE_DIR}/settings")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/settings/mod.rs"), "pub struct Settings;\n").unwrap();
+		let original_output:Vec<(String, String)> = read_files_recursive(Path::new(CRATE_DIR));
+		let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_undo_journal(JOURNAL_DIR);
+		assert!(matches!(updater.undo(), Err(Error::Undo { .. })));
+
+		// Converting the layout moves files and regenerates auto-exports in a single run.
+		updater.convert_layout(ModuleLayout::SiblingFile).unwrap();
+		assert!(!Path::new(&format!("{CRATE_DIR}/settings")).exists());
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\nmod settings;\n\npub use settings::*; // Settings\n");
+
+		// Undoing restores the moved file and the regenerated file together.
+		assert_eq!(updater.undo().unwrap().len(), 3);
+		assert_eq!(read_files_recursive(Path::new(CRATE_DIR)), original_output);
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_unit_test_files() {
+		const CRATE_DIR:&str = "target/crate_manager/test_unit_test_files_crate";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(CRATE_DIR).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/a.rs"), "pub fn function_a() {}\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/a_u.rs"), "pub fn test_helper() {}\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/b_test.rs"), "pub fn function_b() {}\n").unwrap();
+
+		// Default suffix.
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+		ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).generate().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\nmod b_test;\n#[cfg(test)]\nmod a_u;\nmod a;\n\npub use b_test::*; // function_b\npub use a::*; // function_a\n");
+
+		// Custom suffix.
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+		ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_test_file_suffix("_test").generate().unwrap();
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\n#[cfg(test)]\nmod b_test;\nmod a_u;\nmod a;\n\npub use a_u::*; // test_helper\npub use a::*; // function_a\n");
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_cfg_gating() {
+		const CRATE_DIR:&str = "target/crate_manager/test_cfg_crate";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(format!("{CRATE_DIR}/features")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/clipboard_linux.rs"), "pub struct Clipboard;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/clipboard_windows.rs"), "pub struct Clipboard;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/features/mod.rs"), "// auto-exports\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/features/serde.rs"), "pub fn encode() {}\n").unwrap();
+
+		// Generate with the default mapping.
+		let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs"));
+		updater.generate().unwrap();
+		assert!(updater.diagnostics().is_empty());
+		assert_eq!(
+			std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(),
+			"// auto-exports\n#[cfg(target_os = \"windows\")]\nmod clipboard_windows;\n#[cfg(target_os = \"linux\")]\nmod clipboard_linux;\nmod features;\n\n#[cfg(target_os = \"windows\")]\npub use clipboard_windows::*; // Clipboard\n#[cfg(target_os = \"linux\")]\npub use clipboard_linux::*; // Clipboard\npub use features::*; // encode\n"
+		);
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/features/mod.rs")).unwrap(), "// auto-exports\n#[cfg(feature = \"serde\")]\nmod serde;\n\n#[cfg(feature = \"serde\")]\npub use serde::*; // encode\n");
+
+		// Generate with a custom mapping.
+		ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_cfg_suffixes(&[("_linux", "unix")]).with_feature_dir("").generate().unwrap();
+		assert!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap().starts_with("// auto-exports\nmod clipboard_windows;\n#[cfg(unix)]\nmod clipboard_linux;\nmod features;\n"));
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/features/mod.rs")).unwrap(), "// auto-exports\nmod serde;\n\npub use serde::*; // encode\n");
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+}
\ No newline at end of file
@@ -3,8 +3,25 @@ pub(super) const AUTO_EXPORTS_TAG:&str = "auto-exports";
 
 
 // auto-exports
+#[cfg(test)]
+mod item_import_export_updater_u;
 mod item_import_export_updater;
 mod item_import_export_parser;
+#[cfg(test)]
+mod ignore_patterns_u;
+mod ignore_patterns;
+#[cfg(test)]
+mod parse_cache_u;
+#[cfg(test)]
+mod rust_lexer_u;
+mod parse_cache;
+mod rust_lexer;
 
-pub use item_import_export_updater::*; // ItemImportExportUpdater
-pub use item_import_export_parser::*; // MODULE_IMPORT_TAG, PARSER_EXPORT_TAG, PARSER_PUB_TYPE_TAG, PARSER_TYPE_TAG, PARSER_IDENTIFIER_TAG, PARSER_AUTO_EXPORTS_TRIGGER_TAG, imports_exports_parser
\ No newline at end of file
+pub use item_import_export_updater::*; // DEFAULT_TEST_FILE_SUFFIX, DEFAULT_CFG_SUFFIXES, DEFAULT_FEATURE_DIR, ModuleScaffolding, ModuleLayout, ItemImportExportUpdater
+pub(crate) use item_import_export_updater::*; // is_test_file
+pub use item_import_export_parser::*; // MODULE_IMPORT_TAG, PARSER_EXPORT_TAG, PARSER_PUB_TYPE_TAG, PARSER_TYPE_TAG, PARSER_IDENTIFIER_TAG, PARSER_AUTO_EXPORTS_TRIGGER_TAG, imports_exports_parser
+pub(crate) use item_import_export_parser::*; // parse_file_contents
+pub use ignore_patterns::*; // IGNORE_FILE_NAME, GITIGNORE_FILE_NAME, IgnorePatterns
+pub use parse_cache::*; // DEFAULT_PARSE_CACHE_FILE, ParseCache
+pub(crate) use parse_cache::*; // CACHE_FORMAT_VERSION, content_hash, join_fields, split_fields
+pub(crate) use rust_lexer::*; // TokenKind, Token, RustLexer, block_length, token_length
\ No newline at end of file
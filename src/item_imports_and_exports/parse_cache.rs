@@ -0,0 +1,185 @@
+use crate::{ Error, Export, Import, ParsedFile, PubType, Span };
+use std::{ collections::HashMap, path::Path };
+use file_ref::FileRef;
+
+
+
+pub const DEFAULT_PARSE_CACHE_FILE:&str = "target/crate_manager/parse_cache.txt";
+const PARSE_CACHE_HEADER:&str = "crate_manager parse cache";
+const TOOL_VERSION:&str = env!("CARGO_PKG_VERSION");
+pub(crate) const CACHE_FORMAT_VERSION:u32 = 2; // Bump whenever the parser output changes, so cached results of older parsers are discarded.
+
+
+
+pub struct ParseCache {
+	file:String,
+	fingerprint:String,
+	entries:HashMap<String, (u64, ParsedFile)>,
+	modified:bool
+}
+impl ParseCache {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Open the parse cache stored in the given file. As the cache can always be rebuilt, a missing, outdated or unreadable cache file results in an empty cache.
+	pub fn open(file:&str) -> ParseCache {
+		let mut cache:ParseCache = ParseCache {
+			file: file.to_string(),
+			fingerprint: String::new(),
+			entries: HashMap::new(),
+			modified: false
+		};
+		if let Ok(contents) = FileRef::new(file).read() {
+			if cache.load(&contents).is_none() {
+				cache.entries.clear();
+				cache.modified = true;
+			}
+		}
+		cache
+	}
+
+	/// Load the entries from the contents of a cache file. Returns None if the contents are invalid or were created by another version of the tool.
+	fn load(&mut self, contents:&str) -> Option<()> {
+		let mut lines = contents.split('\n');
+		let header:Vec<String> = split_fields(lines.next()?);
+		if header.len() != 3 || header[0] != PARSE_CACHE_HEADER || header[1] != TOOL_VERSION {
+			return None;
+		}
+		self.fingerprint = header[2].clone();
+
+		let mut current_entry:Option<(String, u64, ParsedFile)> = None;
+		for line in lines.filter(|line| !line.is_empty()) {
+			let fields:Vec<String> = split_fields(line);
+			match fields[0].as_str() {
+				"F" => {
+					if let Some((path, hash, parsed_file)) = current_entry.take() {
+						self.entries.insert(path, (hash, parsed_file));
+					}
+					let auto_exports_trigger:Option<usize> = if fields.get(3)?.is_empty() { None } else { Some(fields[3].parse().ok()?) };
+					current_entry = Some((fields.get(1)?.clone(), u64::from_str_radix(fields.get(2)?, 16).ok()?, ParsedFile { imports: Vec::new(), exports: Vec::new(), auto_exports_trigger }));
+				},
+				"I" => {
+					let (pub_type, struct_type, identifier, span) = decode_item(&fields)?;
+					current_entry.as_mut()?.2.imports.push(Import { pub_type, struct_type, identifier, span });
+				},
+				"E" => {
+					let (pub_type, struct_type, identifier, span) = decode_item(&fields)?;
+					current_entry.as_mut()?.2.exports.push(Export { pub_type, struct_type, identifier, span });
+				},
+				_ => return None
+			}
+		}
+		if let Some((path, hash, parsed_file)) = current_entry {
+			self.entries.insert(path, (hash, parsed_file));
+		}
+		Some(())
+	}
+
+
+	/* USAGE METHODS */
+
+	/// Make sure the cache was created with the given configuration fingerprint. Clears the cache if it was not.
+	pub(crate) fn validate(&mut self, fingerprint:&str) {
+		if self.fingerprint != fingerprint {
+			self.fingerprint = fingerprint.to_string();
+			self.entries.clear();
+			self.modified = true;
+		}
+	}
+
+	/// Get the cached parse results of a file, if the cached contents hash matches the given hash.
+	pub(crate) fn get(&self, path:&str, content_hash:u64) -> Option<&ParsedFile> {
+		self.entries.get(path).filter(|(hash, _)| *hash == content_hash).map(|(_, parsed_file)| parsed_file)
+	}
+
+	/// Store the parse results of a file.
+	pub(crate) fn insert(&mut self, path:&str, content_hash:u64, parsed_file:ParsedFile) {
+		self.entries.insert(path.to_string(), (content_hash, parsed_file));
+		self.modified = true;
+	}
+
+	/// Write the cache to its file if it was modified. Entries of files that no longer exist are discarded.
+	pub fn save(&mut self) -> Result<(), Error> {
+		if !self.modified {
+			return Ok(());
+		}
+		self.entries.retain(|path, _| FileRef::new(path).exists());
+
+		// Create contents.
+		let mut lines:Vec<String> = vec![join_fields(&[PARSE_CACHE_HEADER, TOOL_VERSION, &self.fingerprint])];
+		let mut paths:Vec<&String> = self.entries.keys().collect();
+		paths.sort();
+		for path in paths {
+			let (hash, parsed_file) = &self.entries[path];
+			lines.push(join_fields(&["F", path, &format!("{hash:x}"), &parsed_file.auto_exports_trigger.map(|cursor| cursor.to_string()).unwrap_or_default()]));
+			lines.extend(parsed_file.imports.iter().map(|import| encode_item("I", &import.pub_type, &import.struct_type, &import.identifier, &import.span)));
+			lines.extend(parsed_file.exports.iter().map(|export| encode_item("E", &export.pub_type, &export.struct_type, &export.identifier, &export.span)));
+		}
+
+		// Write file.
+		if let Some(dir) = Path::new(&self.file).parent() {
+			std::fs::create_dir_all(dir).map_err(|error| Error::io(&self.file, error))?;
+		}
+		FileRef::new(&self.file).write(lines.join("\n")).map_err(|error| Error::io(&self.file, error))?;
+		self.modified = false;
+		Ok(())
+	}
+}
+
+
+
+/// Create a hash of the contents of a file. Uses FNV-1a, which is stable between runs and compiler versions.
+pub(crate) fn content_hash(contents:&str) -> u64 {
+	contents.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Encode an import or export into a cache line.
+fn encode_item(tag:&str, pub_type:&Option<PubType>, struct_type:&str, identifier:&str, span:&Span) -> String {
+	join_fields(&[
+		tag,
+		pub_type.as_ref().map(|pub_type| pub_type.to_str()).unwrap_or_default(),
+		struct_type,
+		identifier,
+		&span.start.to_string(),
+		&span.end.to_string(),
+		&span.line.to_string(),
+		&span.column.to_string()
+	])
+}
+
+/// Decode an import or export from the fields of a cache line.
+fn decode_item(fields:&[String]) -> Option<(Option<PubType>, String, String, Span)> {
+	if fields.len() != 8 {
+		return None;
+	}
+	let pub_type:Option<PubType> = if fields[1].is_empty() { None } else { Some(PubType::from_str(&fields[1])) };
+	let span:Span = Span { start: fields[4].parse().ok()?, end: fields[5].parse().ok()?, line: fields[6].parse().ok()?, column: fields[7].parse().ok()? };
+	Some((pub_type, fields[2].clone(), fields[3].clone(), span))
+}
+
+/// Join fields into a single tab-separated line, escaping tabs, newlines and backslashes.
+pub(crate) fn join_fields(fields:&[&str]) -> String {
+	fields.iter().map(|field| field.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r")).collect::<Vec<String>>().join("\t")
+}
+
+/// Split a tab-separated line into its unescaped fields.
+pub(crate) fn split_fields(line:&str) -> Vec<String> {
+	line.split('\t').map(|field| {
+		let mut unescaped:String = String::new();
+		let mut chars = field.chars();
+		while let Some(char) = chars.next() {
+			if char == '\\' {
+				unescaped.push(match chars.next() {
+					Some('t') => '\t',
+					Some('n') => '\n',
+					Some('r') => '\r',
+					Some(escaped) => escaped,
+					None => '\\'
+				});
+			} else {
+				unescaped.push(char);
+			}
+		}
+		unescaped
+	}).collect()
+}
\ No newline at end of file
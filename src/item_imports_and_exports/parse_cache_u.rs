@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ content_hash, parse_file_contents, ParseCache, ParsedFile };
+	use file_ref::FileRef;
+
+
+	const TEST_CACHE_FILE:&str = "target/crate_manager/test_parse_cache.txt";
+	const TEST_SOURCE_FILE:&str = "src/lib.rs";
+	const TEST_SOURCE:&str = "use std::fmt::Display;\n\n// auto-exports\nmod data;\n\npub(crate) struct Data\t{}\npub fn parse_data() {}";
+
+
+	#[test]
+	fn test_parse_cache_round_trip() {
+		let parsed_file:ParsedFile = parse_file_contents(TEST_SOURCE_FILE, TEST_SOURCE).unwrap();
+		let hash:u64 = content_hash(TEST_SOURCE);
+
+		// Store and save.
+		let mut cache:ParseCache = ParseCache::open(TEST_CACHE_FILE);
+		cache.validate("fingerprint");
+		cache.insert(TEST_SOURCE_FILE, hash, parsed_file.clone());
+		cache.save().unwrap();
+
+		// Reload and compare.
+		let mut cache:ParseCache = ParseCache::open(TEST_CACHE_FILE);
+		cache.validate("fingerprint");
+		let cached_file:&ParsedFile = cache.get(TEST_SOURCE_FILE, hash).unwrap();
+		assert_eq!(cached_file.auto_exports_trigger, parsed_file.auto_exports_trigger);
+		assert_eq!(cached_file.imports.iter().map(|import| import.identifier.clone()).collect::<Vec<String>>(), vec!["std::fmt::Display".to_string(), "data".to_string()]);
+		assert_eq!(cached_file.exports.iter().map(|export| export.identifier.clone()).collect::<Vec<String>>(), vec!["Data".to_string(), "parse_data".to_string()]);
+		assert!(cached_file.exports[0].span == parsed_file.exports[0].span);
+		assert!(cache.get(TEST_SOURCE_FILE, hash + 1).is_none());
+
+		// Changing the fingerprint clears the cache.
+		cache.validate("other fingerprint");
+		assert!(cache.get(TEST_SOURCE_FILE, hash).is_none());
+
+		FileRef::new(TEST_CACHE_FILE).delete().unwrap();
+	}
+}
\ No newline at end of file
@@ -0,0 +1,218 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TokenKind { Identifier, Lifetime, Literal, Comment, Whitespace, Punctuation }
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+	pub(crate) kind:TokenKind,
+	pub(crate) start:usize,
+	pub(crate) end:usize
+}
+
+
+
+pub(crate) struct RustLexer<'a> {
+	bytes:&'a [u8],
+	cursor:usize
+}
+impl<'a> RustLexer<'a> {
+
+	/// Create a new lexer over the given text. Tokens never split a multi-byte character, so their offsets can always be used to slice the text.
+	pub fn new(text:&'a str) -> RustLexer<'a> {
+		RustLexer {
+			bytes: text.as_bytes(),
+			cursor: 0
+		}
+	}
+
+	/// Get the byte at the given offset from the cursor, or 0 if out of bounds.
+	fn peek(&self, offset:usize) -> u8 {
+		self.bytes.get(self.cursor + offset).copied().unwrap_or(0)
+	}
+
+	/// Whether or not the byte can be part of an identifier. Non-ASCII bytes are treated as identifier bytes, so multi-byte characters are never split.
+	fn is_identifier_byte(byte:u8) -> bool {
+		byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+	}
+
+	/// Move the cursor past all bytes matching the condition.
+	fn skip_while<T:Fn(u8) -> bool>(&mut self, condition:T) {
+		while self.cursor < self.bytes.len() && condition(self.bytes[self.cursor]) {
+			self.cursor += 1;
+		}
+	}
+
+	/// Move the cursor past a quoted literal whose opening quote is at the cursor. Handles backslash escapes.
+	fn skip_quoted(&mut self, quote:u8) {
+		self.cursor += 1;
+		while self.cursor < self.bytes.len() {
+			match self.bytes[self.cursor] {
+				b'\\' => self.cursor += 2,
+				byte if byte == quote => {
+					self.cursor += 1;
+					return;
+				},
+				_ => self.cursor += 1
+			}
+		}
+		self.cursor = self.bytes.len();
+	}
+
+	/// Move the cursor past a raw string whose hash signs or opening quote are at the cursor. Returns false if there is no raw string at the cursor.
+	fn skip_raw_string(&mut self) -> bool {
+		let mut hash_count:usize = 0;
+		while self.peek(hash_count) == b'#' {
+			hash_count += 1;
+		}
+		if self.peek(hash_count) != b'"' {
+			return false;
+		}
+		self.cursor += hash_count + 1;
+		while self.cursor < self.bytes.len() {
+			if self.bytes[self.cursor] == b'"' && (1..=hash_count).all(|offset| self.peek(offset) == b'#') {
+				self.cursor += 1 + hash_count;
+				return true;
+			}
+			self.cursor += 1;
+		}
+		true
+	}
+
+	/// Move the cursor past a block comment whose opening is at the cursor. Handles nested comments.
+	fn skip_block_comment(&mut self) {
+		let mut depth:usize = 0;
+		while self.cursor < self.bytes.len() {
+			if self.peek(0) == b'/' && self.peek(1) == b'*' {
+				depth += 1;
+				self.cursor += 2;
+			} else if self.peek(0) == b'*' && self.peek(1) == b'/' {
+				depth -= 1;
+				self.cursor += 2;
+				if depth == 0 {
+					return;
+				}
+			} else {
+				self.cursor += 1;
+			}
+		}
+	}
+
+	/// Move the cursor past a character literal or lifetime starting at the cursor. Returns the kind of token skipped.
+	fn skip_char_or_lifetime(&mut self) -> TokenKind {
+		if self.peek(1) == b'\\' {
+			self.skip_quoted(b'\'');
+			return TokenKind::Literal;
+		}
+		let char_length:usize = match self.peek(1) {
+			byte if byte < 0x80 => 1,
+			byte if byte >= 0xF0 => 4,
+			byte if byte >= 0xE0 => 3,
+			_ => 2
+		};
+		if self.peek(1 + char_length) == b'\'' {
+			self.cursor += char_length + 2;
+			return TokenKind::Literal;
+		}
+		self.cursor += 1;
+		self.skip_while(Self::is_identifier_byte);
+		TokenKind::Lifetime
+	}
+}
+impl Iterator for RustLexer<'_> {
+	type Item = Token;
+
+	fn next(&mut self) -> Option<Token> {
+		if self.cursor >= self.bytes.len() {
+			return None;
+		}
+		let start:usize = self.cursor;
+		let kind:TokenKind = match (self.peek(0), self.peek(1)) {
+			(byte, _) if byte.is_ascii_whitespace() => {
+				self.skip_while(|byte| byte.is_ascii_whitespace());
+				TokenKind::Whitespace
+			},
+			(b'/', b'/') => {
+				self.skip_while(|byte| byte != b'\n');
+				TokenKind::Comment
+			},
+			(b'/', b'*') => {
+				self.skip_block_comment();
+				TokenKind::Comment
+			},
+			(b'"', _) => {
+				self.skip_quoted(b'"');
+				TokenKind::Literal
+			},
+			(b'\'', _) => self.skip_char_or_lifetime(),
+			(b'b' | b'c', b'"') => {
+				self.cursor += 1;
+				self.skip_quoted(b'"');
+				TokenKind::Literal
+			},
+			(b'b', b'\'') => {
+				self.cursor += 1;
+				self.skip_quoted(b'\'');
+				TokenKind::Literal
+			},
+			(b'r', b'#') if Self::is_identifier_byte(self.peek(2)) => {
+				self.cursor += 2;
+				self.skip_while(Self::is_identifier_byte);
+				TokenKind::Identifier
+			},
+			(b'r', b'"' | b'#') | (b'b' | b'c', b'r') if self.bytes[start] == b'r' || matches!(self.peek(2), b'"' | b'#') => {
+				self.cursor += if self.bytes[start] == b'r' { 1 } else { 2 };
+				if self.skip_raw_string() {
+					TokenKind::Literal
+				} else {
+					self.cursor = start;
+					self.skip_while(Self::is_identifier_byte);
+					TokenKind::Identifier
+				}
+			},
+			(byte, _) if byte.is_ascii_digit() => {
+				self.cursor += 1;
+				while Self::is_identifier_byte(self.peek(0)) || (self.peek(0) == b'.' && self.peek(1).is_ascii_digit()) {
+					self.cursor += 1;
+				}
+				TokenKind::Literal
+			},
+			(byte, _) if Self::is_identifier_byte(byte) => {
+				self.skip_while(Self::is_identifier_byte);
+				TokenKind::Identifier
+			},
+			_ => {
+				self.cursor += 1;
+				TokenKind::Punctuation
+			}
+		};
+		self.cursor = self.cursor.min(self.bytes.len());
+		Some(Token { kind, start, end: self.cursor })
+	}
+}
+
+
+
+/// Find the length of the brace-delimited block at the start of the text, including both braces. Braces inside literals and comments are ignored. Returns None if the text does not start with a brace or the block is never closed.
+pub(crate) fn block_length(text:&str) -> Option<usize> {
+	if !text.starts_with('{') {
+		return None;
+	}
+	let mut depth:usize = 0;
+	for token in RustLexer::new(text).filter(|token| token.kind == TokenKind::Punctuation) {
+		match &text[token.start..token.end] {
+			"{" => depth += 1,
+			"}" => {
+				depth -= 1;
+				if depth == 0 {
+					return Some(token.end);
+				}
+			},
+			_ => {}
+		}
+	}
+	None
+}
+
+/// Find the length of the literal or comment token at the start of the text, if it starts with one of the given kind.
+pub(crate) fn token_length(text:&str, kind:TokenKind) -> Option<usize> {
+	RustLexer::new(text).next().filter(|token| token.kind == kind).map(|token| token.end)
+}
\ No newline at end of file
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ block_length, RustLexer, Token, TokenKind };
+
+
+	/// Lex the text and return the contents and kinds of all non-whitespace tokens.
+	fn lex(text:&str) -> Vec<(&str, TokenKind)> {
+		RustLexer::new(text).filter(|token| token.kind != TokenKind::Whitespace).map(|token:Token| (&text[token.start..token.end], token.kind)).collect()
+	}
+
+
+	#[test]
+	fn test_literals() {
+		assert_eq!(lex(r#""a \" { b" '{' '\'' b'}' '\u{7D}' 'é' 1.5"#), vec![
+			(r#""a \" { b""#, TokenKind::Literal),
+			("'{'", TokenKind::Literal),
+			(r"'\''", TokenKind::Literal),
+			("b'}'", TokenKind::Literal),
+			(r"'\u{7D}'", TokenKind::Literal),
+			("'é'", TokenKind::Literal),
+			("1.5", TokenKind::Literal)
+		]);
+		assert_eq!(lex(r###"r"}" r#"a " } b"# br##"x"#y"## r#type"###), vec![
+			(r#"r"}""#, TokenKind::Literal),
+			(r###"r#"a " } b"#"###, TokenKind::Literal),
+			(r###"br##"x"#y"##"###, TokenKind::Literal),
+			("r#type", TokenKind::Identifier)
+		]);
+	}
+
+	#[test]
+	fn test_lifetimes_and_comments() {
+		assert_eq!(lex("fn f<'a>(x:&'a str) /* { /* } */ } */ // }\n{}"), vec![
+			("fn", TokenKind::Identifier),
+			("f", TokenKind::Identifier),
+			("<", TokenKind::Punctuation),
+			("'a", TokenKind::Lifetime),
+			(">", TokenKind::Punctuation),
+			("(", TokenKind::Punctuation),
+			("x", TokenKind::Identifier),
+			(":", TokenKind::Punctuation),
+			("&", TokenKind::Punctuation),
+			("'a", TokenKind::Lifetime),
+			("str", TokenKind::Identifier),
+			(")", TokenKind::Punctuation),
+			("/* { /* } */ } */", TokenKind::Comment),
+			("// }", TokenKind::Comment),
+			("{", TokenKind::Punctuation),
+			("}", TokenKind::Punctuation)
+		]);
+	}
+
+	#[test]
+	fn test_block_length() {
+		let block:&str = "{ let a = \"}\"; let b = '}'; /* } */ // }\n if a { b } }";
+		assert_eq!(block_length(&format!("{block} pub fn next() {{}}")), Some(block.len()));
+		assert_eq!(block_length("{ fn unclosed() { }"), None);
+		assert_eq!(block_length("fn f() {}"), None);
+	}
+}
\ No newline at end of file
@@ -4,16 +4,24 @@
 fn test() {
 	use crate::ItemImportExportUpdater;
 
+	// Fail on any error-level diagnostic, showing all diagnostics of the step.
+	let assert_no_errors = |step:&str, diagnostics:&[&Diagnostic]| {
+		assert!(!diagnostics.iter().any(|diagnostic| diagnostic.severity == Severity::Error), "{step} reported errors:\n\n{}", render_diagnostics(diagnostics, DiagnosticFormat::Human));
+	};
+
 	// Automatically generate imports and exports.
-	let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new("src/lib.rs");
+	let mut updater:ItemImportExportUpdater = ItemImportExportUpdater::new("src/lib.rs").with_parse_cache(DEFAULT_PARSE_CACHE_FILE).with_undo_journal(DEFAULT_UNDO_JOURNAL_DIR);
 	updater.generate().unwrap();
+	assert_no_errors("generating auto-exports", &updater.diagnostics());
 
 	// Try to automatically generate dependency imports in the TOML file.
 	const LIB_SOURCE_FILE_ENV_NAME:&str = "SFCM_LIB_SRC";
-	if let Ok(source_dir) = std::env::var(LIB_SOURCE_FILE_ENV_NAME) {
-		let libraries_storage:LibrariesStorage = LibrariesStorage::from_file(&source_dir);
-		let found_lib_names:Vec<&str> = updater.recursive_imports().iter().map(|import| import.identifier.split("::").next().unwrap()).collect::<Vec<&str>>();
-		generate_toml_imports("Cargo.toml", &found_lib_names, &libraries_storage).unwrap();
+	if let Ok(source) = std::env::var(LIB_SOURCE_FILE_ENV_NAME) {
+		let libraries_storage:LibrariesStorage = if std::path::Path::new(&source).is_dir() { LibrariesStorage::from_directory(&source, true) } else { LibrariesStorage::from_file(&source) }.unwrap();
+		assert_no_errors("loading library sources", &libraries_storage.diagnostics().iter().collect::<Vec<&Diagnostic>>());
+		let usages:Vec<LibraryUsage> = LibraryUsageScanner::new(".").with_updater_config(&updater).scan().unwrap();
+		let diagnostics:Vec<Diagnostic> = generate_toml_imports("Cargo.toml", &usages, &libraries_storage).unwrap();
+		assert_no_errors("generating library imports", &diagnostics.iter().collect::<Vec<&Diagnostic>>());
 	}
 }
 
@@ -23,7 +31,23 @@ fn test() {
 mod item_imports_and_exports;
 mod library_imports;
 mod data_structs;
+mod diagnostics;
+mod file_writes;
+#[cfg(test)]
+mod span_u;
+mod error;
+mod watch;
+mod span;
 
-pub use item_imports_and_exports::*; // ItemImportExportUpdater, MODULE_IMPORT_TAG, PARSER_EXPORT_TAG, PARSER_PUB_TYPE_TAG, PARSER_TYPE_TAG, PARSER_IDENTIFIER_TAG, PARSER_AUTO_EXPORTS_TRIGGER_TAG, imports_exports_parser
-pub use library_imports::*; // generate_toml_imports, LibrariesStorage, Library
-pub(crate) use data_structs::*; // Import, Export
\ No newline at end of file
+pub use item_imports_and_exports::*; // DEFAULT_TEST_FILE_SUFFIX, DEFAULT_CFG_SUFFIXES, DEFAULT_FEATURE_DIR, ModuleScaffolding, ModuleLayout, ItemImportExportUpdater, MODULE_IMPORT_TAG, PARSER_EXPORT_TAG, PARSER_PUB_TYPE_TAG, PARSER_TYPE_TAG, PARSER_IDENTIFIER_TAG, PARSER_AUTO_EXPORTS_TRIGGER_TAG, imports_exports_parser, IGNORE_FILE_NAME, GITIGNORE_FILE_NAME, IgnorePatterns, DEFAULT_PARSE_CACHE_FILE, ParseCache
+pub(crate) use item_imports_and_exports::*; // is_test_file, parse_file_contents, CACHE_FORMAT_VERSION, content_hash, join_fields, split_fields, TokenKind, Token, RustLexer, block_length, token_length
+pub use library_imports::*; // TomlImportOptions, generate_toml_imports, generate_toml_imports_with_options, UsageContext, LibraryUsage, LibraryUsageScanner, library_usages, LibrariesStorage, GitReference, Library, parse_git_url, TomlDocument, TomlValue, TomlStatement, read_toml, statement_span, read_toml_statements, Version, VersionOperator, VersionComparator, VersionReq, VersionSelection
+pub(crate) use library_imports::*; // canonical_crate_name
+pub(crate) use data_structs::*; // PubType, Import, Export, ParsedFile
+pub use diagnostics::*; // render_diagnostics, DIAGNOSTIC_EXPORT_COLLISION, DIAGNOSTIC_UNRESOLVED_MODULE, DIAGNOSTIC_MISSING_LIBRARY, DIAGNOSTIC_UNUSED_LIBRARY, DIAGNOSTIC_INVALID_MANIFEST, DIAGNOSTIC_UNREADABLE_GIT_CONFIG, Severity, DiagnosticFormat, Diagnostic
+pub use file_writes::*; // FileTransaction, DEFAULT_UNDO_JOURNAL_DIR, UndoJournal
+pub use error::*; // Error
+pub use watch::*; // WatchReport, AutoExportsWatcher, FileChange, FileWatcher
+pub(crate) use watch::*; // debounce_changes
+pub use span::*; // Span
+pub(crate) use span::*; // LineIndex
\ No newline at end of file
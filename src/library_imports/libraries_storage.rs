@@ -1,27 +1,58 @@
+use crate::{ Diagnostic, DIAGNOSTIC_INVALID_MANIFEST, DIAGNOSTIC_UNREADABLE_GIT_CONFIG, Error, read_toml, read_toml_statements, statement_span, TomlStatement, TomlValue, VersionReq, VersionSelection };
 use file_ref::FileRef;
 
 
 
-const GIT_URL_TAG:&str = "github.com";
+const GIT_URL_SCHEMES:&[&str] = &["https://", "http://", "ssh://", "git://", "file://"];
+const GIT_URL_PREFIX:&str = "git+";
+const GIT_URL_SUFFIX:&str = ".git";
+const LOCAL_GIT_URL_SCHEME:&str = "file://";
+const MANIFEST_FILE_NAME:&str = "Cargo.toml";
+const GIT_CONFIG_FILE:&str = ".git/config";
+const SKIPPED_DIR_NAMES:&[&str] = &["target"];
+const TOML_SOURCE_EXTENSION:&str = "toml";
 
 
 
 pub struct LibrariesStorage {
-	pub(crate) libraries:Vec<Library>
+	pub(crate) libraries:Vec<Library>,
+	diagnostics:Vec<Diagnostic>
 }
 impl LibrariesStorage {
 
 	/// Create a new library storage.
 	pub fn new(libraries:Vec<Library>) -> LibrariesStorage {
-		let mut storage:LibrariesStorage = LibrariesStorage { libraries };
+		let mut storage:LibrariesStorage = LibrariesStorage { libraries, diagnostics: Vec::new() };
 		storage.combine_libraries();
 		storage
 	}
 
-	/// Create a library storage from the contents of a file.
-	pub fn from_file(file:&str) -> LibrariesStorage {
+	/// Create a library storage from the contents of a file. Files in TOML format list each library as a table with the same fields as a Cargo dependency. Other files are read as TOML if possible and otherwise as the legacy line format, listing a name, a git url or local path and an optional version number on each line.
+	pub fn from_file(file:&str) -> Result<LibrariesStorage, Error> {
+		let contents:String = FileRef::new(file).read().map_err(|error| Error::io(file, error))?;
+		match read_toml_statements(file, &contents) {
+			Ok((source, statements)) => LibrariesStorage::from_toml(file, &source, &statements),
+			Err(error) if FileRef::new(file).extension() == Some(TOML_SOURCE_EXTENSION) => Err(error),
+			Err(_) => Ok(LibrariesStorage::from_lines(&contents))
+		}
+	}
+
+	/// Create a library storage from a TOML library source. A library can be a table or a version string.
+	fn from_toml(file:&str, source:&TomlValue, statements:&[TomlStatement]) -> Result<LibrariesStorage, Error> {
+		let mut libraries:Vec<Library> = Vec::new();
+		for (name, value) in source.as_table().unwrap_or_default() {
+			libraries.push(match value {
+				TomlValue::String(version) => Library::new(name, "").with_version_number(version),
+				TomlValue::Table(_) => Library::from_toml(file, name, value, statements)?,
+				_ => return Err(Error::config(file, statement_span(statements, &[name]), &format!("library `{name}` should be a version string or a table")))
+			});
+		}
+		Ok(LibrariesStorage::new(libraries))
+	}
+
+	/// Create a library storage from the legacy line format.
+	fn from_lines(contents:&str) -> LibrariesStorage {
 		let mut libraries:Vec<Library> = Vec::new();
-		let contents:String = FileRef::new(file).read().unwrap_or_default();
 		for line in contents.split('\n').map(|line| line.trim()) {
 			if !line.is_empty() {
 				let line_chars:Vec<char> = line.chars().collect();
@@ -33,11 +64,74 @@ impl LibrariesStorage {
 		LibrariesStorage::new(libraries)
 	}
 
+	/// Create a library storage from all crates in a directory and its sub-directories. Each package manifest registers a local library with the package name, version and lib name. When recording git remotes, libraries inside a git repository also get the url of its `origin` remote, or its first remote if there is no origin, which is only imported when the local path is not known. Build output and hidden directories are skipped, as are crates with an invalid manifest, which are reported in the diagnostics of the storage.
+	pub fn from_directory(dir:&str, record_git_remotes:bool) -> Result<LibrariesStorage, Error> {
+		let mut libraries:Vec<Library> = Vec::new();
+		let mut diagnostics:Vec<Diagnostic> = Vec::new();
+		LibrariesStorage::scan_directory(&FileRef::new(dir.trim_end_matches('/')), record_git_remotes, None, &mut libraries, &mut diagnostics)?;
+		Ok(LibrariesStorage { diagnostics, ..LibrariesStorage::new(libraries) })
+	}
+
+	/// Add libraries for all crates in the directory and its sub-directories. The git url is the remote of the repository the directory is in, if any. Crates with a manifest that cannot be parsed are skipped with a diagnostic, as are git configs that cannot be read, in which case the remote of the enclosing repository is kept.
+	fn scan_directory(dir:&FileRef, record_git_remotes:bool, git_url:Option<&str>, libraries:&mut Vec<Library>, diagnostics:&mut Vec<Diagnostic>) -> Result<(), Error> {
+
+		// Find the remote of a repository starting in this directory.
+		let git_config:FileRef = FileRef::new(&format!("{}/{GIT_CONFIG_FILE}", dir.path()));
+		let repository_url:Option<String> = if record_git_remotes && git_config.exists() {
+			match git_config.read() {
+				Ok(contents) => git_remote_url(&contents),
+				Err(error) => {
+					diagnostics.push(Diagnostic::warning(DIAGNOSTIC_UNREADABLE_GIT_CONFIG, &format!("could not read the git remote of the repository in {}", dir.path()), git_config.path()).with_label(&error.to_string()));
+					None
+				}
+			}
+		} else {
+			None
+		};
+		let git_url:Option<&str> = repository_url.as_deref().or(git_url);
+
+		// Register the crate in this directory.
+		let manifest_file:FileRef = FileRef::new(&format!("{}/{MANIFEST_FILE_NAME}", dir.path()));
+		if manifest_file.exists() {
+			let contents:String = manifest_file.read().map_err(|error| Error::io(manifest_file.path(), error))?;
+			let manifest:TomlValue = match read_toml(manifest_file.path(), &contents) {
+				Ok(manifest) => manifest,
+				Err(error) => {
+					let diagnostic:Diagnostic = Diagnostic::warning(DIAGNOSTIC_INVALID_MANIFEST, &format!("skipped the crate in {}, its manifest could not be parsed", dir.path()), manifest_file.path()).with_label(error.message());
+					diagnostics.push(match error.span() {
+						Some(span) => diagnostic.with_span(span.clone()),
+						None => diagnostic
+					});
+					TomlValue::Table(Vec::new())
+				}
+			};
+			if let Some(name) = manifest.get_path(&["package", "name"]).and_then(|name| name.as_str()) {
+				let mut library:Library = Library::local(name, dir.path());
+				if let Some(version) = manifest.get_path(&["package", "version"]).and_then(|version| version.as_str()) {
+					library = library.with_version_number(version);
+				}
+				if let Some(lib_name) = manifest.get_path(&["lib", "name"]).and_then(|lib_name| lib_name.as_str()) {
+					library = library.with_lib_name(lib_name);
+				}
+				if let Some(git_url) = git_url {
+					library.git_url = Some(git_url.to_string());
+				}
+				libraries.push(library);
+			}
+		}
+
+		// Scan sub-directories.
+		for sub_dir in dir.list_dirs().into_iter().filter(|sub_dir| !sub_dir.name().starts_with('.') && !SKIPPED_DIR_NAMES.contains(&sub_dir.name())) {
+			LibrariesStorage::scan_directory(&sub_dir, record_git_remotes, git_url, libraries, diagnostics)?;
+		}
+		Ok(())
+	}
+
 	/// Combine libraries when possible.
 	fn combine_libraries(&mut self) {
 		let mut source_index:usize = 0;
 		while source_index < self.libraries.len() {
-			while let Some(target_offset) = self.libraries[source_index + 1..].iter().position(|library| self.libraries[source_index].name == library.name) {
+			while let Some(target_offset) = self.libraries[source_index + 1..].iter().position(|library| canonical_crate_name(&self.libraries[source_index].name) == canonical_crate_name(&library.name)) {
 				let target:Library = self.libraries.remove(source_index + 1 + target_offset);
 				self.libraries[source_index].combine_with(target);
 			}
@@ -45,9 +139,38 @@ impl LibrariesStorage {
 		}
 	}
 
-	/// Find a library by name.
+	/// Get the diagnostics found while creating the storage, like crates that were skipped.
+	pub fn diagnostics(&self) -> &[Diagnostic] {
+		&self.diagnostics
+	}
+
+	/// Find a library by its package name or the name it is imported by in code. Hyphens and underscores are treated as equal.
 	pub fn find(&self, library_name:&str) -> Option<&Library> {
-		self.libraries.iter().find(|library| library.name == library_name)
+		let library_name:String = canonical_crate_name(library_name);
+		self.libraries.iter().find(|library| canonical_crate_name(&library.lib_name) == library_name).or_else(|| self.libraries.iter().find(|library| canonical_crate_name(&library.name) == library_name))
+	}
+}
+
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitReference { Branch(String), Tag(String), Rev(String) }
+impl GitReference {
+
+	/// Get the name of the Cargo dependency field for this reference.
+	pub fn field_name(&self) -> &'static str {
+		match self {
+			GitReference::Branch(_) => "branch",
+			GitReference::Tag(_) => "tag",
+			GitReference::Rev(_) => "rev"
+		}
+	}
+
+	/// Get the branch name, tag name or commit hash.
+	pub fn value(&self) -> &str {
+		match self {
+			GitReference::Branch(value) | GitReference::Tag(value) | GitReference::Rev(value) => value
+		}
 	}
 }
 
@@ -56,9 +179,15 @@ impl LibrariesStorage {
 #[derive(Clone)]
 pub struct Library {
 	pub(crate) name:String,
+	pub(crate) lib_name:String,
 	pub(crate) versions:Vec<String>,
 	pub(crate) git_url:Option<String>,
-	pub(crate) local_path:Option<String>
+	pub(crate) git_reference:Option<GitReference>,
+	pub(crate) local_path:Option<String>,
+	pub(crate) registry:Option<String>,
+	pub(crate) features:Vec<String>,
+	pub(crate) default_features:bool,
+	pub(crate) optional:bool
 }
 impl Library {
 
@@ -66,56 +195,140 @@ impl Library {
 
 	/// Create a new library. Tries to determine type by source.
 	pub fn new(name:&str, source:&str) -> Library {
+		let words:Vec<&str> = source.split_whitespace().collect();
+		let is_source = |source:&str| source.is_empty() || parse_git_url(source).is_some() || FileRef::new(source).exists();
+		let is_explicit_requirement = |requirement:&str| requirement.starts_with(['=', '>', '<', '^', '~']) || requirement.contains('.');
 
-		// Recurse with trailing version number.
-		if let Some(version) = source.split(" ").last().filter(|word| word.split(".").filter(|s| s.chars().all(|char| char.is_numeric())).count() == 3) {
-			Library::new(name, source[..source.len() - version.len()].trim()).with_version_number(version)
-		}
-		
 		// Git url.
-		else if source.contains(GIT_URL_TAG) {
+		if parse_git_url(source).is_some() {
 			Library::git(name, source)
 		}
 		
 		// Local path.
-		else if FileRef::new(source).exists() {
+		else if !source.is_empty() && FileRef::new(source).exists() {
 			Library::local(name, source)
 		}
+
+		// Recurse with trailing version requirement. The requirement can contain spaces, so the longest trailing sequence of words that forms a requirement is used. Words like `x` or `2` are only a requirement when following a valid source, otherwise an explicit operator or a dotted version is required.
+		else if let Some(version_start) = (0..words.len()).find(|start| {
+			let requirement:String = words[*start..].join(" ");
+			VersionReq::parse(&requirement).is_some() && (is_source(&words[..*start].join(" ")) || is_explicit_requirement(&requirement))
+		}) {
+			Library::new(name, &words[..version_start].join(" ")).with_version_number(&words[version_start..].join(" "))
+		}
 		
 		// Only from name.
 		else {
 			Library {
 				name: name.to_string(),
+				lib_name: canonical_crate_name(name),
 				versions: Vec::new(),
 				git_url: None,
-				local_path: None
+				git_reference: None,
+				local_path: None,
+				registry: None,
+				features: Vec::new(),
+				default_features: true,
+				optional: false
 			}
 		}
 	}
 	
-	/// Create a new git-imported library.
+	/// Create a new git-imported library. The url is normalised and a reference in the url is used as git reference.
 	pub fn git(name:&str, git_url:&str) -> Library {
+		let (git_url, git_reference) = parse_git_url(git_url).unwrap_or((git_url.to_string(), None));
 		Library {
-			name: name.to_string(),
-			versions: Vec::new(),
-			git_url: Some(git_url.to_string()),
-			local_path: None
+			git_url: Some(git_url),
+			git_reference,
+			..Library::new(name, "")
 		}
 	}
 
 	/// Create a new library from a local path.
 	pub fn local(name:&str, path:&str) -> Library {
 		Library {
-			name: name.to_string(),
-			versions: Vec::new(),
-			git_url: None,
-			local_path: Some(path.to_string())
+			local_path: Some(path.to_string()),
+			..Library::new(name, "")
+		}
+	}
+
+	/// Create a new library from a table in a TOML library source. Accepts the fields of a Cargo dependency, with `lib` for the name the library is imported by in code. The git url is normalised like in string sources, an explicit `branch`, `tag` or `rev` field takes precedence over a reference in the url.
+	fn from_toml(file:&str, name:&str, source:&TomlValue, statements:&[TomlStatement]) -> Result<Library, Error> {
+		let field_error = |field:&str, expected:&str| Error::config(file, statement_span(statements, &[name, field]), &format!("field `{field}` of library `{name}` should be {expected}"));
+		let mut library:Library = Library::new(name, "");
+		for (field, value) in source.as_table().unwrap_or_default() {
+			let string_value = || value.as_str().map(|value| value.to_string()).ok_or_else(|| field_error(field, "a string"));
+			let bool_value = || value.as_bool().ok_or_else(|| field_error(field, "a boolean"));
+			match field.as_str() {
+				"version" => library = library.with_version_number(&string_value()?),
+				"git" => {
+					let (git_url, git_reference) = parse_git_url(&string_value()?).ok_or_else(|| field_error(field, "a git url"))?;
+					library.git_url = Some(git_url);
+					if library.git_reference.is_none() {
+						library.git_reference = git_reference;
+					}
+				},
+				"branch" => library.git_reference = Some(GitReference::Branch(string_value()?)),
+				"tag" => library.git_reference = Some(GitReference::Tag(string_value()?)),
+				"rev" => library.git_reference = Some(GitReference::Rev(string_value()?)),
+				"path" => library.local_path = Some(string_value()?),
+				"registry" => library.registry = Some(string_value()?),
+				"lib" => library.lib_name = string_value()?,
+				"features" => {
+					let features:Option<Vec<&str>> = value.as_array().and_then(|items| items.iter().map(|item| item.as_str()).collect());
+					library = library.with_features(&features.ok_or_else(|| field_error(field, "an array of strings"))?);
+				},
+				"default-features" => library.default_features = bool_value()?,
+				"optional" => library.optional = bool_value()?,
+				_ => return Err(Error::config(file, statement_span(statements, &[name, field]), &format!("library `{name}` has unknown field `{field}`")))
+			}
+		}
+		Ok(library)
+	}
+
+	/// Return self with the name the library is imported by in code, if it differs from the package name.
+	pub fn with_lib_name(mut self, lib_name:&str) -> Self {
+		self.lib_name = lib_name.to_string();
+		self
+	}
+
+	/// Return self with a git branch, tag or revision to use.
+	pub fn with_git_reference(mut self, reference:GitReference) -> Self {
+		self.git_reference = Some(reference);
+		self
+	}
+
+	/// Return self with the registry to download the library from.
+	pub fn with_registry(mut self, registry:&str) -> Self {
+		self.registry = Some(registry.to_string());
+		self
+	}
+
+	/// Return self with additional features enabled.
+	pub fn with_features(mut self, features:&[&str]) -> Self {
+		for feature in features {
+			if !self.features.iter().any(|existing| existing == feature) {
+				self.features.push(feature.to_string());
+			}
 		}
+		self
 	}
 
-	/// Return self with a specific version number.
+	/// Return self without the default features of the library.
+	pub fn without_default_features(mut self) -> Self {
+		self.default_features = false;
+		self
+	}
+
+	/// Return self as an optional dependency.
+	pub fn as_optional(mut self) -> Self {
+		self.optional = true;
+		self
+	}
+
+	/// Return self with a specific version number or requirement. Versions are kept free of duplicates and ordered from lowest to highest.
 	pub fn with_version_number(mut self, version:&str) -> Self {
-		self.versions.push(version.to_string());
+		self.add_version(version);
 		self
 	}
 
@@ -124,33 +337,107 @@ impl Library {
 
 	/// Combine this library with another.
 	pub fn combine_with(&mut self, other:Library) {
-		self.versions.extend(other.versions);
+		for version in &other.versions {
+			self.add_version(version);
+		}
 		if let Some(git_url) = other.git_url {
 			self.git_url = Some(git_url);
 		}
 		if let Some(local_path) = other.local_path {
 			self.local_path = Some(local_path);
 		}
+		if canonical_crate_name(&other.lib_name) != canonical_crate_name(&other.name) {
+			self.lib_name = other.lib_name;
+		}
+		if let Some(git_reference) = other.git_reference {
+			self.git_reference = Some(git_reference);
+		}
+		if let Some(registry) = other.registry {
+			self.registry = Some(registry);
+		}
+		for feature in other.features {
+			if !self.features.contains(&feature) {
+				self.features.push(feature);
+			}
+		}
+		self.default_features &= other.default_features;
+		self.optional |= other.optional;
+	}
+
+	/// Add a version number or requirement, unless an equal one is already known. Keeps versions ordered by the lowest version they allow.
+	fn add_version(&mut self, version:&str) {
+		let version:&str = version.trim().trim_matches('"');
+		let requirement:Option<VersionReq> = VersionReq::parse(version);
+		if self.versions.iter().any(|existing| existing == version || (requirement.is_some() && VersionReq::parse(existing) == requirement)) {
+			return;
+		}
+		self.versions.push(version.to_string());
+		self.versions.sort_by_key(|version| VersionReq::parse(version).map(|requirement| requirement.minimum_version()));
+	}
+
+	/// Select the version to import the library with. The existing requirement is a requirement for the library already found in the manifest, used by the compatible selection.
+	pub fn select_version(&self, selection:VersionSelection, existing_requirement:Option<&VersionReq>) -> Option<String> {
+		let highest:&String = self.versions.last()?;
+		match selection {
+			VersionSelection::Highest => Some(highest.clone()),
+			VersionSelection::Compatible => {
+				let compatible:Option<&String> = existing_requirement.and_then(|existing_requirement| {
+					self.versions.iter().rev().find(|version| VersionReq::parse(version).is_some_and(|requirement| existing_requirement.matches(&requirement.minimum_version())))
+				});
+				Some(compatible.unwrap_or(highest).clone())
+			},
+			VersionSelection::Pinned => Some(match VersionReq::parse(highest) {
+				Some(requirement) => VersionReq::exact(&requirement.minimum_version()).to_string(),
+				None => highest.clone()
+			})
+		}
+	}
+
+	/// Get the key the library should be listed under in the manifest. This is the package name, unless the library is imported by a name that does not match it.
+	pub fn dependency_key(&self) -> &str {
+		if canonical_crate_name(&self.lib_name) == canonical_crate_name(&self.name) { &self.name } else { &self.lib_name }
 	}
 
 	/// Create a string that can be used for importing the library. Contains the name of the import and can be pasted directly into the Cargo.Toml.
 	pub fn as_import_string(&self) -> String {
-		self.name.clone() + &self.as_import_value()
+		self.dependency_key().to_string() + &self.as_import_value()
 	}
 
-	/// Create a value that can be used for importing the library. Does not contain the name of the import.
+	/// Create a value that can be used for importing the library, using the highest known version. Does not contain the name of the import.
 	pub fn as_import_value(&self) -> String {
+		self.as_import_value_with_selection(VersionSelection::Highest, None)
+	}
+
+	/// Create a value that can be used for importing the library, using the given version selection. Does not contain the name of the import. Cargo only allows a single source, so a local path takes precedence over a git url.
+	pub fn as_import_value_with_selection(&self, selection:VersionSelection, existing_requirement:Option<&VersionReq>) -> String {
 		const QUOTED:fn(&str) -> String = |source| format!("\"{}\"", source.trim().trim_matches('"'));
 
 		let mut properties:Vec<(&'static str, String)> = Vec::new();
-		if let Some(version) = self.versions.last() {
-			properties.push(("version", QUOTED(version)));
+		if let Some(version) = self.select_version(selection, existing_requirement) {
+			properties.push(("version", QUOTED(&version)));
 		}
 		if let Some(local_path) = &self.local_path {
 			properties.push(("path", QUOTED(local_path)));
-		}
-		if let Some(git_url) = &self.git_url {
+		} else if let Some(git_url) = &self.git_url {
 			properties.push(("git", QUOTED(git_url)));
+			if let Some(git_reference) = &self.git_reference {
+				properties.push((git_reference.field_name(), QUOTED(git_reference.value())));
+			}
+		}
+		if let Some(registry) = &self.registry {
+			properties.push(("registry", QUOTED(registry)));
+		}
+		if self.dependency_key() != self.name {
+			properties.push(("package", QUOTED(&self.name)));
+		}
+		if !self.features.is_empty() {
+			properties.push(("features", format!("[{}]", self.features.iter().map(|feature| QUOTED(feature)).collect::<Vec<String>>().join(", "))));
+		}
+		if !self.default_features {
+			properties.push(("default-features", "false".to_string()));
+		}
+		if self.optional {
+			properties.push(("optional", "true".to_string()));
 		}
 		format!(
 			"{} {} {}",
@@ -159,4 +446,68 @@ impl Library {
 			'}'
 		)
 	}
+}
+
+
+
+/// Get the canonical form of a crate name for comparison. Cargo treats hyphens in package names as underscores in code.
+pub(crate) fn canonical_crate_name(name:&str) -> String {
+	name.replace('-', "_")
+}
+
+/// Find the url of the `origin` remote in the contents of a git config file, or the first remote if there is no origin. Remotes that are not git urls, like local paths, are ignored.
+fn git_remote_url(contents:&str) -> Option<String> {
+	let mut remotes:Vec<(&str, &str)> = Vec::new();
+	let mut remote_name:Option<&str> = None;
+	for line in contents.lines().map(|line| line.trim()) {
+		if line.starts_with('[') {
+			remote_name = line.strip_prefix("[remote \"").and_then(|line| line.strip_suffix("\"]"));
+		} else if let (Some(name), Some((key, value))) = (remote_name, line.split_once('=')) {
+			if key.trim() == "url" {
+				remotes.push((name, value.trim().trim_matches('"')));
+			}
+		}
+	}
+	let url:&str = remotes.iter().find(|(name, _)| *name == "origin").or(remotes.first()).map(|(_, url)| *url)?;
+	parse_git_url(url).map(|(url, _)| url)
+}
+
+/// Parse a git remote into a normalised url and an optional reference. Accepts urls with a `http`, `https`, `ssh`, `git` or `file` scheme, optionally prefixed with `git+`, and scp-like remotes like `git@host:owner/repo`, which are converted to `ssh` urls. A trailing `.git` is stripped, except from local repositories. A `#rev` fragment or a `?branch=`, `?tag=` or `?rev=` query becomes the reference.
+pub fn parse_git_url(source:&str) -> Option<(String, Option<GitReference>)> {
+	let source:&str = source.trim();
+	let source:&str = source.strip_prefix(GIT_URL_PREFIX).unwrap_or(source);
+	if source.is_empty() || source.contains(char::is_whitespace) {
+		return None;
+	}
+
+	// Split off the reference.
+	let (url, fragment) = source.split_once('#').map(|(url, fragment)| (url, Some(fragment))).unwrap_or((source, None));
+	let (url, query) = url.split_once('?').map(|(url, query)| (url, Some(query))).unwrap_or((url, None));
+	let mut git_reference:Option<GitReference> = query.and_then(|query| query.split('&').find_map(|parameter| match parameter.split_once('=')? {
+		("branch", value) => Some(GitReference::Branch(value.to_string())),
+		("tag", value) => Some(GitReference::Tag(value.to_string())),
+		("rev", value) => Some(GitReference::Rev(value.to_string())),
+		_ => None
+	}));
+	if let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) {
+		git_reference = Some(GitReference::Rev(fragment.to_string()));
+	}
+
+	// Convert scp-like remotes to ssh urls.
+	let url:String = if let Some(scheme) = GIT_URL_SCHEMES.iter().find(|scheme| url.starts_with(**scheme)) {
+		if url.len() == scheme.len() {
+			return None;
+		}
+		url.to_string()
+	} else {
+		match url.split_once(':') {
+			Some((user_host, path)) if user_host.contains('@') && !user_host.contains('/') && !path.is_empty() && !path.starts_with('/') => format!("ssh://{user_host}/{path}"),
+			_ => return None
+		}
+	};
+
+	// Strip trailing slashes and the git suffix.
+	let url:&str = url.trim_end_matches('/');
+	let url:&str = if url.starts_with(LOCAL_GIT_URL_SCHEME) { url } else { url.strip_suffix(GIT_URL_SUFFIX).unwrap_or(url) };
+	Some((url.to_string(), git_reference))
 }
\ No newline at end of file
@@ -2,7 +2,7 @@
 mod tests {
 	use file_ref::FileRef;
 
-use crate::{LibrariesStorage, Library};
+use crate::{DIAGNOSTIC_INVALID_MANIFEST, DIAGNOSTIC_UNREADABLE_GIT_CONFIG, GitReference, LibrariesStorage, Library, parse_git_url, VersionReq, VersionSelection};
 
 
 	fn test_libs() -> Vec<Library> {
@@ -96,7 +96,7 @@ use crate::{LibrariesStorage, Library};
 		".to_string()).unwrap();
 
 		// Create and validate libraries storage.
-		let storage:LibrariesStorage = LibrariesStorage::from_file(file.path());
+		let storage:LibrariesStorage = LibrariesStorage::from_file(file.path()).unwrap();
 
 		assert_eq!(storage.libraries[0].name, "local_lib_auto");
 		assert_eq!(storage.libraries[0].local_path, Some("Cargo.toml".to_string()));
@@ -121,4 +121,202 @@ use crate::{LibrariesStorage, Library};
 		// Delete temporary file.
 		file.delete().unwrap();
 	}
+
+	#[test]
+	fn test_package_and_lib_names() {
+		let storage:LibrariesStorage = LibrariesStorage::new(vec![
+			Library::git("my-crate", "https://github.com/my-crate"),
+			Library::git("renamed-package", "https://github.com/renamed-package").with_lib_name("renamed")
+		]);
+
+		// Hyphens and underscores are treated as equal.
+		let library:&Library = storage.find("my_crate").unwrap();
+		assert_eq!(library.name, "my-crate");
+		assert_eq!(library.lib_name, "my_crate");
+		assert_eq!(library.dependency_key(), "my-crate");
+		assert_eq!(library.as_import_value(), "{ git=\"https://github.com/my-crate\" }");
+
+		// A lib name that does not match the package name is used as key, with the package named explicitly.
+		let library:&Library = storage.find("renamed").unwrap();
+		assert_eq!(library.dependency_key(), "renamed");
+		assert_eq!(library.as_import_string(), "renamed{ git=\"https://github.com/renamed-package\", package=\"renamed-package\" }");
+		assert!(storage.find("renamed_package").is_some());
+	}
+
+	#[test]
+	fn test_library_creation_from_toml_file() {
+
+		// Create temporary file.
+		let file:FileRef = FileRef::new("target/temp_source.toml");
+		file.write("
+			short_lib = \"1.2.3\"
+
+			[full_lib]
+			version = \"4.5.6\"
+			git = \"https://github.com/full_lib\"
+			branch = \"develop\"
+			features = [\"derive\", \"std\"]
+			default-features = false
+			optional = true
+
+			[registry-lib]
+			version = \"0.1.0\"
+			registry = \"internal\"
+			lib = \"registry_library\"
+		".to_string()).unwrap();
+
+		// Create and validate libraries storage.
+		let storage:LibrariesStorage = LibrariesStorage::from_file(file.path()).unwrap();
+		assert_eq!(storage.find("short_lib").unwrap().as_import_value(), "{ version=\"1.2.3\" }");
+
+		let full_lib:&Library = storage.find("full_lib").unwrap();
+		assert_eq!(full_lib.git_reference, Some(GitReference::Branch("develop".to_string())));
+		assert_eq!(full_lib.as_import_value(), "{ version=\"4.5.6\", git=\"https://github.com/full_lib\", branch=\"develop\", features=[\"derive\", \"std\"], default-features=false, optional=true }");
+
+		let registry_lib:&Library = storage.find("registry_library").unwrap();
+		assert_eq!(registry_lib.as_import_string(), "registry_library{ version=\"0.1.0\", registry=\"internal\", package=\"registry-lib\" }");
+
+		// Git urls are normalised like in string sources, with a fragment as reference unless one is given explicitly.
+		file.write("
+			scp_lib = { git = \"git@codeberg.org:owner/scp_lib.git#v2\" }
+			explicit_lib = { git = \"https://github.com/explicit_lib.git#v2\", branch = \"main\" }
+		".to_string()).unwrap();
+		let storage:LibrariesStorage = LibrariesStorage::from_file(file.path()).unwrap();
+		assert_eq!(storage.find("scp_lib").unwrap().as_import_value(), "{ git=\"ssh://git@codeberg.org/owner/scp_lib\", rev=\"v2\" }");
+		assert_eq!(storage.find("explicit_lib").unwrap().as_import_value(), "{ git=\"https://github.com/explicit_lib\", branch=\"main\" }");
+
+		// Invalid fields are reported at their location.
+		let error_line = |contents:&str| {
+			file.write(contents.to_string()).unwrap();
+			LibrariesStorage::from_file(file.path()).err().and_then(|error| error.span().map(|span| span.line))
+		};
+		assert_eq!(error_line("[lib]\nfeatures = \"derive\"\n"), Some(2));
+		assert_eq!(error_line("[lib]\nversion = \"1.0\"\nunknown = true\n"), Some(3));
+		assert_eq!(error_line("lib = { version = \"1.0\", unknown = true }\n"), Some(1));
+		assert_eq!(error_line("other = \"1.0\"\nlib = 5\n"), Some(2));
+		assert_eq!(error_line("[lib]\nversion = \"1.0\"\ngit = \"not a url\"\n"), Some(3));
+
+		// Delete temporary file.
+		file.delete().unwrap();
+	}
+
+	#[test]
+	fn test_library_versions() {
+
+		// Versions and requirements are recognized after the source.
+		assert_eq!(Library::new("lib", "Cargo.toml 1.0").versions, vec!["1.0".to_string()]);
+		assert_eq!(Library::new("lib", "https://github.com/lib 0.3.0-alpha.1").versions, vec!["0.3.0-alpha.1".to_string()]);
+		assert_eq!(Library::new("lib", "https://github.com/lib ^1.2").git_url, Some("https://github.com/lib".to_string()));
+		let library:Library = Library::new("lib", "Cargo.toml >=1, <2");
+		assert_eq!(library.local_path, Some("Cargo.toml".to_string()));
+		assert_eq!(library.versions, vec![">=1, <2".to_string()]);
+
+		// Bare words are only a requirement after a valid source, otherwise an explicit operator or a dotted version is required.
+		assert_eq!(Library::new("lib", "2").versions, vec!["2".to_string()]);
+		assert_eq!(Library::new("lib", "Cargo.toml *").versions, vec!["*".to_string()]);
+		assert_eq!(Library::new("lib", "unknown_source 2").versions, Vec::<String>::new());
+		assert_eq!(Library::new("lib", "unknown_source ^2").versions, vec!["^2".to_string()]);
+		assert_eq!(Library::new("lib", "unknown_source 2.1").versions, vec!["2.1".to_string()]);
+
+		// An existing path is never taken as a requirement.
+		std::fs::create_dir_all("target/crate_manager/test_library_versions/x").unwrap();
+		let library:Library = Library::new("lib", "target/crate_manager/test_library_versions/x");
+		assert_eq!(library.local_path, Some("target/crate_manager/test_library_versions/x".to_string()));
+		assert_eq!(library.versions, Vec::<String>::new());
+		std::fs::remove_dir_all("target/crate_manager/test_library_versions").unwrap();
+
+		// Versions are deduplicated and ordered when combined.
+		let mut library:Library = Library::new("lib", "2.0.0").with_version_number("1.10.0");
+		library.combine_with(Library::new("lib", "1.9").with_version_number("2.0.0").with_version_number("1.10.0-beta.1"));
+		assert_eq!(library.versions, vec!["1.9".to_string(), "1.10.0-beta.1".to_string(), "1.10.0".to_string(), "2.0.0".to_string()]);
+
+		// The version to import depends on the selection.
+		let existing_requirement:VersionReq = VersionReq::parse("1.9").unwrap();
+		assert_eq!(library.select_version(VersionSelection::Highest, None), Some("2.0.0".to_string()));
+		assert_eq!(library.select_version(VersionSelection::Compatible, Some(&existing_requirement)), Some("1.10.0".to_string()));
+		assert_eq!(library.select_version(VersionSelection::Compatible, None), Some("2.0.0".to_string()));
+		assert_eq!(library.select_version(VersionSelection::Pinned, None), Some("=2.0.0".to_string()));
+		assert_eq!(Library::new("lib", "").select_version(VersionSelection::Highest, None), None);
+	}
+
+	#[test]
+	fn test_git_sources() {
+		let git_url = |source:&str| parse_git_url(source).map(|(url, _)| url);
+
+		// Remotes on any host are detected and normalised.
+		assert_eq!(git_url("https://gitlab.com/group/lib.git"), Some("https://gitlab.com/group/lib".to_string()));
+		assert_eq!(git_url("https://codeberg.org/owner/lib/"), Some("https://codeberg.org/owner/lib".to_string()));
+		assert_eq!(git_url("git+https://git.internal.net/lib"), Some("https://git.internal.net/lib".to_string()));
+		assert_eq!(git_url("ssh://git@git.internal.net:2222/lib.git"), Some("ssh://git@git.internal.net:2222/lib".to_string()));
+		assert_eq!(git_url("git@gitlab.com:group/lib.git"), Some("ssh://git@gitlab.com/group/lib".to_string()));
+		assert_eq!(git_url("file:///srv/repos/lib.git"), Some("file:///srv/repos/lib.git".to_string()));
+
+		// Paths and versions are not git remotes.
+		assert_eq!(git_url("C:/lib"), None);
+		assert_eq!(git_url("../lib"), None);
+		assert_eq!(git_url("1.2.3"), None);
+		assert_eq!(git_url("https://"), None);
+
+		// References are taken from the fragment or query.
+		assert_eq!(parse_git_url("https://gitlab.com/group/lib.git#3f2a1b"), Some(("https://gitlab.com/group/lib".to_string(), Some(GitReference::Rev("3f2a1b".to_string())))));
+		assert_eq!(parse_git_url("https://gitlab.com/group/lib?branch=develop").unwrap().1, Some(GitReference::Branch("develop".to_string())));
+		assert_eq!(parse_git_url("https://gitlab.com/group/lib?tag=v1.0").unwrap().1, Some(GitReference::Tag("v1.0".to_string())));
+
+		// Libraries from any git remote are imported from git.
+		let library:Library = Library::new("lib", "git@codeberg.org:owner/lib.git#v2 1.0");
+		assert_eq!(library.as_import_value(), "{ version=\"1.0\", git=\"ssh://git@codeberg.org/owner/lib\", rev=\"v2\" }");
+	}
+
+	#[test]
+	fn test_library_creation_from_directory() {
+
+		// Create temporary crates.
+		let root:&str = "target/crate_manager/test_libraries_from_directory";
+		let _ = std::fs::remove_dir_all(root);
+		for (dir, manifest) in [
+			("tools/first", "[package]\nname = \"first-crate\"\nversion = \"0.2.0\"\n"),
+			("tools/second", "[package]\nname = \"second\"\nversion = \"1.0.0\"\n\n[lib]\nname = \"second_lib\"\n"),
+			("tools/first/target/package", "[package]\nname = \"build_output\"\n"),
+			("workspace", "[workspace]\nmembers = []\n"),
+			("tools/broken", "[package\nname = \"broken\"\n")
+		] {
+			std::fs::create_dir_all(format!("{root}/{dir}")).unwrap();
+			std::fs::write(format!("{root}/{dir}/Cargo.toml"), manifest).unwrap();
+		}
+		std::fs::create_dir_all(format!("{root}/tools/.git")).unwrap();
+		std::fs::write(format!("{root}/tools/.git/config"), "[core]\n\tbare = false\n[remote \"upstream\"]\n\turl = https://gitlab.com/upstream/tools\n[remote \"origin\"]\n\turl = git@gitlab.com:group/tools.git\n").unwrap();
+
+		// Create and validate libraries storage.
+		let storage:LibrariesStorage = LibrariesStorage::from_directory(root, true).unwrap();
+		assert_eq!(storage.libraries.len(), 2);
+		let first:&Library = storage.find("first_crate").unwrap();
+		assert_eq!(first.local_path, Some(format!("{root}/tools/first")));
+		assert_eq!(first.versions, vec!["0.2.0".to_string()]);
+		assert_eq!(first.git_url, Some("ssh://git@gitlab.com/group/tools".to_string()));
+		assert_eq!(first.as_import_value(), format!("{{ version=\"0.2.0\", path=\"{root}/tools/first\" }}"));
+		let second:&Library = storage.find("second_lib").unwrap();
+		assert_eq!(second.name, "second");
+		assert_eq!(second.versions, vec!["1.0.0".to_string()]);
+
+		// The crate with an invalid manifest is skipped and reported.
+		assert!(storage.find("broken").is_none());
+		assert_eq!(storage.diagnostics().len(), 1);
+		assert_eq!(storage.diagnostics()[0].code, DIAGNOSTIC_INVALID_MANIFEST);
+		assert_eq!(storage.diagnostics()[0].span.as_ref().map(|span| span.line), Some(1));
+
+		// An unreadable git config is reported, keeping the remote of the enclosing repository.
+		std::fs::create_dir_all(format!("{root}/tools/second/.git")).unwrap();
+		std::fs::write(format!("{root}/tools/second/.git/config"), [0xFF, 0xFE]).unwrap();
+		let storage:LibrariesStorage = LibrariesStorage::from_directory(root, true).unwrap();
+		assert_eq!(storage.find("second").unwrap().git_url, Some("ssh://git@gitlab.com/group/tools".to_string()));
+		assert_eq!(storage.diagnostics().len(), 2);
+		assert!(storage.diagnostics().iter().any(|diagnostic| diagnostic.code == DIAGNOSTIC_UNREADABLE_GIT_CONFIG));
+
+		// Git remotes are only recorded when requested.
+		let storage:LibrariesStorage = LibrariesStorage::from_directory(root, false).unwrap();
+		assert_eq!(storage.find("second").unwrap().git_url, None);
+
+		// Delete temporary crates.
+		std::fs::remove_dir_all(root).unwrap();
+	}
 }
\ No newline at end of file
@@ -1,78 +1,172 @@
-use crate::{ LibrariesStorage, Library };
-use std::error::Error;
+use crate::{ Diagnostic, DIAGNOSTIC_INVALID_MANIFEST, DIAGNOSTIC_MISSING_LIBRARY, DIAGNOSTIC_UNUSED_LIBRARY, Error, LibrariesStorage, LibraryUsage, canonical_crate_name, TomlDocument, TomlValue, UsageContext, VersionReq, VersionSelection };
 use file_ref::FileRef;
 
 
 
-/// Try to find all missing imports and insert them in the toml file.
-pub fn generate_toml_imports(toml_file:&str, found_lib_names:&[&str], available_libraries:&LibrariesStorage) -> Result<(), Box<dyn Error>> {
-	const INVALID_LIB_NAMES:&[&str] = &["std", "crate", "super"];
+#[derive(Clone, Default)]
+pub struct TomlImportOptions {
+	prune_unused:bool,
+	allowed_unused:Vec<String>,
+	version_selection:VersionSelection
+}
+impl TomlImportOptions {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create new default options. Unused libraries are reported, but not removed.
+	pub fn new() -> TomlImportOptions {
+		TomlImportOptions::default()
+	}
+
+	/// Return self with unused libraries removed from the manifest.
+	pub fn with_pruning(mut self) -> Self {
+		self.prune_unused = true;
+		self
+	}
+
+	/// Return self with libraries that are never reported or removed as unused, like libraries only used through features.
+	pub fn with_allowed_unused(mut self, names:&[&str]) -> Self {
+		self.allowed_unused.extend(names.iter().map(|name| name.to_string()));
+		self
+	}
+
+	/// Return self with a policy for selecting the version of newly added libraries. Defaults to the highest known version.
+	pub fn with_version_selection(mut self, selection:VersionSelection) -> Self {
+		self.version_selection = selection;
+		self
+	}
+}
+
+
+
+/// Try to find all missing imports and insert them in the toml file, using default options. Returns diagnostics for imports that could not be resolved, libraries that are not used and a manifest that could not be parsed.
+pub fn generate_toml_imports(toml_file:&str, usages:&[LibraryUsage], available_libraries:&LibrariesStorage) -> Result<Vec<Diagnostic>, Error> {
+	generate_toml_imports_with_options(toml_file, usages, available_libraries, &TomlImportOptions::default())
+}
+
+/// Try to find all missing imports and insert them in the toml file. Libraries only used by tests are placed under `[dev-dependencies]` and libraries used by the build script under `[build-dependencies]`. Libraries listed in a table their usage no longer requires are moved to the right one. Unused libraries are removed when pruning is enabled, except libraries whose name in code is unknown, which are only reported as possibly unused. Platform-specific tables like `[target.'cfg(unix)'.dependencies]` are left as they are, a library listed there counts as listed in the matching table but is never reported as unused. Returns diagnostics for imports that could not be resolved and libraries that are not used.
+pub fn generate_toml_imports_with_options(toml_file:&str, usages:&[LibraryUsage], available_libraries:&LibrariesStorage, options:&TomlImportOptions) -> Result<Vec<Diagnostic>, Error> {
+	const INVALID_LIB_NAMES:&[&str] = &["std", "crate", "super", "self"];
 	
 	// Read toml file.
 	let toml_file:FileRef = FileRef::new(toml_file);
-	let toml_contents:String = toml_file.read()?;
-	let mut toml_category_index:usize = 0;
-	let mut toml:Vec<(String, Vec<(String, String)>)> = vec![(String::new(), Vec::new())];
-	for line in toml_contents.split('\n').map(|line| line.trim()) {
-
-		// Empty line.
-		if line.is_empty() {
-			continue;
-		}
-		
-		// Category change.
-		else if line.starts_with('[') && line.ends_with(']') {
-			let toml_category:&str = line[1..line.len() - 1].trim();
-			toml_category_index = match toml.iter().position(|(name, _)| name == toml_category) {
-				Some(index) => index,
-				None => {
-					toml.push((toml_category.to_string(), Vec::new()));
-					toml.len() - 1
-				}
-			};
+	let toml_contents:String = toml_file.read().map_err(|error| Error::io(toml_file.path(), error))?;
+	let mut toml:TomlDocument = match TomlDocument::parse(toml_file.path(), &toml_contents) {
+		Ok(toml) => toml,
+		Err(error) => {
+			let diagnostic:Diagnostic = Diagnostic::error(DIAGNOSTIC_INVALID_MANIFEST, "the manifest could not be parsed, no libraries were imported", toml_file.path()).with_label(error.message());
+			return Ok(vec![match error.span() {
+				Some(span) => diagnostic.with_span(span.clone()),
+				None => diagnostic
+			}]);
 		}
-		
-		// Value.
-		else if line.contains('=') {
-			let name:&str = line.split('=').next().unwrap();
-			let value:&str = line[name.len() + 1..].trim();
-			toml[toml_category_index].1.push((name.trim().to_string(), value.to_string()));
+	};
+	let manifest:TomlValue = toml.value().clone();
+
+	// Find used libraries.
+	let own_crate_name:&str = match manifest.get_path(&["package", "name"]).and_then(|name| name.as_str()) {
+		Some(own_crate_name) => own_crate_name,
+		None => return Err(Error::manifest(toml_file.path(), toml.span(&["package"]), "manifest does not contain a package name"))
+	};
+	let mut used_names:Vec<&str> = Vec::new();
+	for usage in usages.iter().filter(|usage| canonical_crate_name(&usage.name) != canonical_crate_name(own_crate_name) && !INVALID_LIB_NAMES.contains(&usage.name.as_str())) {
+		if !used_names.contains(&usage.name.as_str()) {
+			used_names.push(&usage.name);
 		}
 	}
 
-	// Find missing imports.
-	if let Some((_, package_data)) = toml.iter().find(|(name, _)| name == "package") {
-		if let Some((_, own_crate_name)) = package_data.iter().find(|(name, _)| name == "name") {
-			let existing_imports:Vec<String> = match toml.iter().find(|(name, _)| name == "dependencies") {
-				Some((_, dependencies_ini)) => dependencies_ini.iter().map(|(name, _)| name.to_string()).collect(),
-				None => Vec::new()
+	// Make sure each library is listed in the tables its usages require. A manifest key matches a name used in code if they only differ in hyphens and underscores, or if the library listed under the key is imported by that name.
+	let key_matches = |key:&str, name:&str| canonical_crate_name(key) == canonical_crate_name(name) || available_libraries.find(key).is_some_and(|library| canonical_crate_name(&library.lib_name) == canonical_crate_name(name));
+	let listed_key = |name:&str, context:UsageContext| manifest.get(context.dependencies_table()).and_then(|table| table.keys().into_iter().find(|key| key_matches(key, name)));
+	let listed_for_target = |name:&str, context:UsageContext| manifest.get("target").and_then(|targets| targets.as_table()).unwrap_or_default().iter().any(|(_, target)| target.get(context.dependencies_table()).is_some_and(|table| table.keys().into_iter().any(|key| key_matches(key, name))));
+	let mut unavailable_names:Vec<&str> = Vec::new();
+	let mut modified:bool = false;
+	for name in used_names {
+		let contexts:Vec<UsageContext> = usages.iter().filter(|usage| usage.name == name).map(|usage| usage.context).collect();
+		let needs_normal:bool = contexts.contains(&UsageContext::Normal);
+		let needs_test:bool = contexts.contains(&UsageContext::Test) && !needs_normal;
+		let needs_build:bool = contexts.contains(&UsageContext::Build);
+		let required_contexts:Vec<UsageContext> = [(needs_normal, UsageContext::Normal), (needs_test, UsageContext::Test), (needs_build, UsageContext::Build)].into_iter().filter(|(needed, _)| *needed).map(|(_, context)| context).collect();
+		for context in required_contexts.into_iter().filter(|context| listed_key(name, *context).is_none() && !listed_for_target(name, *context)) {
+
+			// Move the library from a table it is no longer needed in.
+			let source_context:Option<UsageContext> = match context {
+				UsageContext::Normal => Some(UsageContext::Test),
+				UsageContext::Test => Some(UsageContext::Normal),
+				UsageContext::Build => Some(UsageContext::Normal).filter(|_| !needs_normal && !needs_test)
 			};
-			let missing_imports:Vec<&&str> = found_lib_names.iter().filter(|name| *name != &own_crate_name && !INVALID_LIB_NAMES.contains(name) && !existing_imports.iter().any(|existing| existing == *name)).collect();
-
-			// Try to add missing imports.
-			let missing_libraries:Vec<&Library> = missing_imports.iter().map(|name| available_libraries.find(name)).flatten().collect();
-			if !missing_libraries.is_empty() {
-				let dependencies_ini:&mut Vec<(String, String)> = match toml.iter_mut().find(|(name, _)| name == "dependencies") {
-					Some((_, dependencies_ini)) => dependencies_ini,
-					None => {
-						toml.push(("dependencies".to_string(), Vec::new()));
-						&mut toml.last_mut().unwrap().1
-					}
-				};
-				dependencies_ini.extend(missing_libraries.iter().map(|library| (library.name.clone(), library.as_import_value())));
-				
-
-				// Write new file.
-				let new_file_contents:String = toml.iter().filter(|(_, values)| !values.is_empty()).map(|(category_name, category_data)| 
-					format!("[{category_name}]\n{}",
-						category_data.iter().map(|(name, value)| format!("{name}={value}")).collect::<Vec<String>>().join("\n")
-					)
-				).collect::<Vec<String>>().join("\n\n");
-				toml_file.write(new_file_contents).unwrap();
+			let source:Option<(UsageContext, &str)> = source_context.and_then(|source_context| listed_key(name, source_context).map(|key| (source_context, key)));
+			if let Some((source_context, key)) = source {
+				modified |= toml.move_key(source_context.dependencies_table(), context.dependencies_table(), key);
+			}
+
+			// Add the library if it is available, preferring a version compatible with the requirement in another table.
+			else if let Some(library) = available_libraries.find(name) {
+				let existing_requirement:Option<VersionReq> = [UsageContext::Normal, UsageContext::Test, UsageContext::Build].into_iter().find_map(|existing_context| {
+					let existing_value:&TomlValue = manifest.get(existing_context.dependencies_table())?.get(listed_key(name, existing_context)?)?;
+					VersionReq::parse(existing_value.as_str().or_else(|| existing_value.get("version").and_then(|version| version.as_str()))?)
+				});
+				modified |= toml.insert(context.dependencies_table(), library.dependency_key(), &library.as_import_value_with_selection(options.version_selection, existing_requirement.as_ref()));
+			} else if !unavailable_names.contains(&name) {
+				unavailable_names.push(name);
 			}
 		}
 	}
 
-	// Return success.
-	Ok(())
+	// Find libraries that are listed, but no longer used. Normal dependencies are available everywhere except in the build script.
+	let is_used = |key:&str, context:UsageContext| usages.iter().filter(|usage| key_matches(key, &usage.name)).any(|usage| match context {
+		UsageContext::Normal => true,
+		UsageContext::Test => usage.context != UsageContext::Build,
+		UsageContext::Build => usage.context == UsageContext::Build
+	});
+
+	// The name a library is imported by in code is only known from its key if the library is available or renamed by the key. Otherwise the library may be used under another name, like `rust-ini` as `ini`.
+	let is_name_known = |table:&str, key:&str| available_libraries.find(key).is_some() || manifest.get(table).and_then(|table| table.get(key)).is_some_and(|value| value.get("package").is_some());
+	let mut unused_libraries:Vec<(&str, &str, &str, bool, bool)> = Vec::new();
+	for context in [UsageContext::Normal, UsageContext::Test, UsageContext::Build] {
+		let table:&str = context.dependencies_table();
+		let listed_keys:Vec<&str> = manifest.get(table).map(|table| table.keys()).unwrap_or_default();
+		for key in listed_keys.into_iter().filter(|key| !is_used(key, context) && !options.allowed_unused.iter().any(|allowed| canonical_crate_name(allowed) == canonical_crate_name(key))) {
+			let is_name_known:bool = is_name_known(table, key);
+			let (note, is_removed) = if !is_name_known {
+				("the name the library is imported by is unknown, so it was not removed; add it to the library source file or allow it to be unused", false)
+			} else if !options.prune_unused {
+				("enable pruning to remove unused libraries automatically, or allow the library to be unused", false)
+			} else if toml.remove(table, key) {
+				modified = true;
+				("the library was removed from the manifest", true)
+			} else {
+				("the library could not be removed automatically, remove it from the manifest by hand", false)
+			};
+			unused_libraries.push((table, key, note, is_removed, is_name_known));
+		}
+	}
+
+	// Report unused libraries at their location in the final manifest.
+	let unused_diagnostics:Vec<Diagnostic> = unused_libraries.into_iter().map(|(table, key, note, is_removed, is_name_known)| {
+		let message:String = if is_name_known { format!("library `{key}` is listed in `[{table}]` but never used") } else { format!("library `{key}` is listed in `[{table}]` but possibly never used") };
+		let diagnostic:Diagnostic = Diagnostic::warning(DIAGNOSTIC_UNUSED_LIBRARY, &message, toml_file.path()).with_note(note);
+		match toml.span(&[table, key]).filter(|_| !is_removed) {
+			Some(span) => diagnostic.with_span(span).with_label("listed here"),
+			None => diagnostic
+		}
+	}).collect();
+	if modified {
+		toml_file.write(toml.contents()).map_err(|error| Error::io(toml_file.path(), error))?;
+	}
+
+	// Report libraries that are not available, at their first usage if its location is known.
+	let mut diagnostics:Vec<Diagnostic> = unavailable_names.iter().map(|name| {
+		let usage:Option<&LibraryUsage> = usages.iter().find(|usage| usage.name == *name && !usage.file.is_empty());
+		let diagnostic:Diagnostic = Diagnostic::warning(DIAGNOSTIC_MISSING_LIBRARY, &format!("library `{name}` is used but not available"), usage.map(|usage| usage.file.as_str()).unwrap_or(toml_file.path()))
+			.with_note("add the library to the library source file to have it imported automatically");
+		match usage.and_then(|usage| usage.span.clone()) {
+			Some(span) => diagnostic.with_span(span).with_label("used here"),
+			None => diagnostic
+		}
+	}).collect();
+	diagnostics.extend(unused_diagnostics);
+
+	// Return diagnostics.
+	Ok(diagnostics)
 }
\ No newline at end of file
@@ -0,0 +1,176 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ generate_toml_imports_with_options, Diagnostic, LibrariesStorage, Library, LibraryUsage, LibraryUsageScanner, Span, TomlImportOptions, UsageContext, VersionSelection };
+
+
+	/// Write a manifest, generate imports for the given usages and return the new manifest and the diagnostics.
+	fn generate(test_name:&str, manifest:&str, usages:&[LibraryUsage], options:&TomlImportOptions) -> (String, Vec<Diagnostic>) {
+		let test_dir:String = format!("target/crate_manager/test_toml_imports_{test_name}");
+		let _ = std::fs::remove_dir_all(&test_dir);
+		std::fs::create_dir_all(&test_dir).unwrap();
+		let toml_file:String = format!("{test_dir}/Cargo.toml");
+		std::fs::write(&toml_file, manifest).unwrap();
+
+		let libraries:LibrariesStorage = LibrariesStorage::new(vec![
+			Library::git("normal", "https://github.com/normal"),
+			Library::git("tested", "https://github.com/tested"),
+			Library::git("build_tool", "https://github.com/build_tool"),
+			Library::git("hyphen-crate", "https://github.com/hyphen-crate"),
+			Library::new("versioned", "2.0.0").with_version_number("1.2.0").with_version_number("1.4.1")
+		]);
+		let diagnostics:Vec<Diagnostic> = generate_toml_imports_with_options(&toml_file, usages, &libraries, options).unwrap();
+		let result:String = std::fs::read_to_string(&toml_file).unwrap();
+		std::fs::remove_dir_all(&test_dir).unwrap();
+		(result, diagnostics)
+	}
+
+	#[test]
+	fn test_imports_are_routed_by_usage() {
+		let (manifest, _) = generate("routing", "[package]\nname=\"test\"\n\n[dependencies]\n", &[
+			LibraryUsage::new("normal", UsageContext::Normal),
+			LibraryUsage::new("normal", UsageContext::Test),
+			LibraryUsage::new("tested", UsageContext::Test),
+			LibraryUsage::new("build_tool", UsageContext::Build)
+		], &TomlImportOptions::new());
+		assert_eq!(manifest, "[package]\nname=\"test\"\n\n[dependencies]\nnormal={ git=\"https://github.com/normal\" }\n\n[dev-dependencies]\ntested={ git=\"https://github.com/tested\" }\n\n[build-dependencies]\nbuild_tool={ git=\"https://github.com/build_tool\" }\n");
+	}
+
+	#[test]
+	fn test_imports_move_between_tables() {
+		let (manifest, _) = generate("moving", "[package]\nname=\"test\"\n\n[dependencies]\ntested=\"1.0\"\n\n[dev-dependencies]\nnormal=\"2.0\"\n", &[
+			LibraryUsage::new("normal", UsageContext::Normal),
+			LibraryUsage::new("tested", UsageContext::Test)
+		], &TomlImportOptions::new());
+		assert_eq!(manifest, "[package]\nname=\"test\"\n\n[dependencies]\nnormal=\"2.0\"\n\n[dev-dependencies]\ntested=\"1.0\"\n");
+	}
+
+	#[test]
+	fn test_unused_imports_are_pruned() {
+		let original:&str = "[package]\nname=\"test\"\n\n[dependencies]\nnormal=\"1.0\"\ntested=\"1.0\"\nfeature_only=\"1.0\"\n\n[build-dependencies]\nnormal=\"1.0\"\n";
+		let usages:Vec<LibraryUsage> = vec![LibraryUsage::new("normal", UsageContext::Normal), LibraryUsage::new("normal", UsageContext::Test)];
+
+		// Without pruning, unused libraries are only reported.
+		let (manifest, diagnostics) = generate("reporting", original, &usages, &TomlImportOptions::new().with_allowed_unused(&["feature_only"]));
+		assert_eq!(manifest, original);
+		assert_eq!(diagnostics.len(), 2);
+
+		// With pruning, unused libraries are removed.
+		let (manifest, diagnostics) = generate("pruning", original, &usages, &TomlImportOptions::new().with_pruning().with_allowed_unused(&["feature_only"]));
+		assert_eq!(manifest, "[package]\nname=\"test\"\n\n[dependencies]\nnormal=\"1.0\"\nfeature_only=\"1.0\"\n\n[build-dependencies]\n");
+		assert_eq!(diagnostics.len(), 2);
+	}
+
+	#[test]
+	fn test_unknown_names_are_not_pruned() {
+		let original:&str = "[package]\nname=\"test\"\n\n[dependencies]\nrust-ini=\"1.0\"\nrenamed={ package=\"other\", version=\"1.0\" }\n";
+
+		// A library that is not available may be used under another name, so it is only reported as possibly unused. A renamed library is imported by its key.
+		let (manifest, diagnostics) = generate("unknown_names", original, &[LibraryUsage::new("ini", UsageContext::Normal)], &TomlImportOptions::new().with_pruning());
+		assert_eq!(manifest, "[package]\nname=\"test\"\n\n[dependencies]\nrust-ini=\"1.0\"\n");
+		let messages:Vec<&str> = diagnostics.iter().filter(|diagnostic| diagnostic.code == "CM0004").map(|diagnostic| diagnostic.message.as_str()).collect();
+		assert_eq!(messages, vec!["library `rust-ini` is listed in `[dependencies]` but possibly never used", "library `renamed` is listed in `[dependencies]` but never used"]);
+	}
+
+	#[test]
+	fn test_target_tables_are_left_alone() {
+		let original:&str = "[package]\nname=\"test\"\n\n[dependencies]\n\n[target.'cfg(unix)'.dependencies]\nnormal=\"1.0\"\nbuild_tool=\"1.0\"\n";
+		let (manifest, diagnostics) = generate("target_tables", original, &[LibraryUsage::new("normal", UsageContext::Normal)], &TomlImportOptions::new().with_pruning());
+		assert_eq!(manifest, original);
+		assert!(diagnostics.is_empty());
+	}
+
+	#[test]
+	fn test_hyphenated_package_names() {
+		let usages:Vec<LibraryUsage> = vec![LibraryUsage::new("hyphen_crate", UsageContext::Normal), LibraryUsage::new("listed_crate", UsageContext::Normal)];
+		let (manifest, diagnostics) = generate("hyphens", "[package]\nname=\"test-crate\"\n\n[dependencies]\nlisted-crate=\"1.0\"\n", &usages, &TomlImportOptions::new());
+		assert_eq!(manifest, "[package]\nname=\"test-crate\"\n\n[dependencies]\nhyphen-crate={ git=\"https://github.com/hyphen-crate\" }\nlisted-crate=\"1.0\"\n");
+		assert!(diagnostics.is_empty());
+	}
+
+	#[test]
+	fn test_version_selection() {
+		let original:&str = "[package]\nname=\"test\"\n\n[dependencies]\nversioned=\"1.2\"\n";
+		let usages:Vec<LibraryUsage> = vec![LibraryUsage::new("versioned", UsageContext::Normal), LibraryUsage::new("versioned", UsageContext::Build)];
+		let expected_manifest = |version:&str| format!("{original}\n[build-dependencies]\nversioned={{ version=\"{version}\" }}\n");
+
+		let (manifest, _) = generate("highest_version", original, &usages, &TomlImportOptions::new());
+		assert_eq!(manifest, expected_manifest("2.0.0"));
+
+		let (manifest, _) = generate("compatible_version", original, &usages, &TomlImportOptions::new().with_version_selection(VersionSelection::Compatible));
+		assert_eq!(manifest, expected_manifest("1.4.1"));
+
+		let (manifest, _) = generate("pinned_version", original, &usages, &TomlImportOptions::new().with_version_selection(VersionSelection::Pinned));
+		assert_eq!(manifest, expected_manifest("=2.0.0"));
+	}
+
+	#[test]
+	fn test_dotted_and_inline_dependencies() {
+		let original:&str = "[package]\nname=\"test\"\n\n[dependencies]\nnormal.version=\"1.0\"\nnormal.default-features=false\nbuild_tool={ version=\"1.0\",\n\tfeatures=[\"first\"] }\n\n[dev-dependencies.tested]\nversion=\"1.0\"\n";
+		let usages:Vec<LibraryUsage> = vec![LibraryUsage::new("normal", UsageContext::Normal), LibraryUsage::new("tested", UsageContext::Test)];
+		let (manifest, diagnostics) = generate("dotted_pruning", original, &usages, &TomlImportOptions::new().with_pruning());
+		assert_eq!(manifest, "[package]\nname=\"test\"\n\n[dependencies]\nnormal.version=\"1.0\"\nnormal.default-features=false\n\n[dev-dependencies.tested]\nversion=\"1.0\"\n");
+		assert_eq!(diagnostics.len(), 1);
+	}
+
+	#[test]
+	fn test_invalid_manifest_is_reported() {
+		let original:&str = "[package]\nname=\"test\"\n\n[dependencies]\nnormal=\"1.0\"\nnormal=\"2.0\"\n";
+		let (manifest, diagnostics) = generate("invalid_manifest", original, &[LibraryUsage::new("tested", UsageContext::Normal)], &TomlImportOptions::new());
+		assert_eq!(manifest, original);
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].code, "CM0005");
+		assert_eq!(diagnostics[0].span.as_ref().map(|span| span.line), Some(6));
+	}
+
+	#[test]
+	fn test_failed_pruning_is_reported() {
+		let original:&str = "dependencies={ tested=\"1.0\" }\n\n[package]\nname=\"test\"\n";
+		let (manifest, diagnostics) = generate("failed_pruning", original, &[], &TomlImportOptions::new().with_pruning());
+		assert_eq!(manifest, original);
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].notes, vec!["the library could not be removed automatically, remove it from the manifest by hand".to_string()]);
+	}
+
+	#[test]
+	fn test_diagnostic_locations() {
+		let original:&str = "[package]\nname=\"test\"\n\n[dependencies]\nnormal=\"1.0\"\ntested=\"1.0\"\n";
+		let usage_span:Span = Span { start: 4, end: 10, line: 1, column: 5 };
+		let usages:Vec<LibraryUsage> = vec![LibraryUsage::new("normal", UsageContext::Normal), LibraryUsage::new("absent", UsageContext::Normal).with_file("src/lib.rs").with_span(usage_span.clone())];
+		let (_, diagnostics) = generate("locations", original, &usages, &TomlImportOptions::new());
+		let missing:&Diagnostic = diagnostics.iter().find(|diagnostic| diagnostic.code == "CM0003").unwrap();
+		assert_eq!((missing.file.as_str(), missing.span.as_ref()), ("src/lib.rs", Some(&usage_span)));
+		let unused:&Diagnostic = diagnostics.iter().find(|diagnostic| diagnostic.code == "CM0004").unwrap();
+		assert_eq!(unused.span.as_ref().map(|span| (span.line, span.column)), Some((6, 1)));
+
+		// Removed libraries have no location left to point at.
+		let (_, diagnostics) = generate("removed_location", original, &usages, &TomlImportOptions::new().with_pruning());
+		assert!(diagnostics.iter().find(|diagnostic| diagnostic.code == "CM0004").unwrap().span.is_none());
+	}
+
+	#[test]
+	fn test_missing_library_is_rendered_at_its_usage() {
+		const CRATE_DIR:&str = "target/crate_manager/test_toml_imports_rendering";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(format!("{CRATE_DIR}/src")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/Cargo.toml"), "[package]\nname=\"test\"\n\n[dependencies]\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/lib.rs"), "fn run() {\n\tabsent::call();\n}\n").unwrap();
+
+		let usages:Vec<LibraryUsage> = LibraryUsageScanner::new(CRATE_DIR).scan().unwrap();
+		let diagnostics:Vec<Diagnostic> = generate_toml_imports_with_options(&format!("{CRATE_DIR}/Cargo.toml"), &usages, &LibrariesStorage::new(Vec::new()), &TomlImportOptions::new()).unwrap();
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(
+			diagnostics[0].render(),
+			[
+				"warning[CM0003]: library `absent` is used but not available".to_string(),
+				format!(" --> {}:2:2", diagnostics[0].file),
+				"  |".to_string(),
+				"2 |     absent::call();".to_string(),
+				"  |     ^^^^^^ used here".to_string(),
+				"  |".to_string(),
+				"  = note: add the library to the library source file to have it imported automatically".to_string()
+			].join("\n")
+		);
+		assert!(diagnostics[0].file.ends_with("src/lib.rs"));
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+}
\ No newline at end of file
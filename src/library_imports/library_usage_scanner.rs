@@ -0,0 +1,300 @@
+use crate::{ is_test_file, DEFAULT_TEST_FILE_SUFFIX, Error, ItemImportExportUpdater, LineIndex, RustLexer, Span, Token, TokenKind };
+use file_ref::FileRef;
+
+
+
+const SOURCE_DIR:&str = "src";
+const TEST_DIRS:&[&str] = &["tests", "benches", "examples"];
+const BUILD_SCRIPT_FILE:&str = "build.rs";
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UsageContext { Normal, Test, Build }
+impl UsageContext {
+
+	/// Get the name of the manifest table libraries used in this context belong in.
+	pub fn dependencies_table(&self) -> &'static str {
+		match self {
+			UsageContext::Normal => "dependencies",
+			UsageContext::Test => "dev-dependencies",
+			UsageContext::Build => "build-dependencies"
+		}
+	}
+}
+
+
+
+#[derive(Debug, Clone)]
+pub struct LibraryUsage {
+	pub name:String,
+	pub context:UsageContext,
+	pub file:String,
+	pub span:Option<Span>
+}
+impl LibraryUsage {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new library usage without location.
+	pub fn new(name:&str, context:UsageContext) -> LibraryUsage {
+		LibraryUsage { name: name.to_string(), context, file: String::new(), span: None }
+	}
+
+	/// Return self with the file the library is used in.
+	pub fn with_file(mut self, file:&str) -> Self {
+		self.file = file.to_string();
+		self
+	}
+
+	/// Return self with the location of the usage in its file.
+	pub fn with_span(mut self, span:Span) -> Self {
+		self.span = Some(span);
+		self
+	}
+}
+impl PartialEq for LibraryUsage {
+
+	/// Usages are equal when they use the same library in the same context. The location only tells where the usage was first found.
+	fn eq(&self, other:&LibraryUsage) -> bool {
+		self.name == other.name && self.context == other.context
+	}
+}
+
+
+
+pub struct LibraryUsageScanner {
+	crate_dir:String,
+	test_file_suffix:String
+}
+impl LibraryUsageScanner {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new scanner for the crate in the given directory.
+	pub fn new(crate_dir:&str) -> LibraryUsageScanner {
+		LibraryUsageScanner {
+			crate_dir: crate_dir.trim_end_matches('/').to_string(),
+			test_file_suffix: DEFAULT_TEST_FILE_SUFFIX.to_string()
+		}
+	}
+
+	/// Return self with the unit test file convention of the given updater, so both agree on which files are test files. Usages in these files are only needed by tests.
+	pub fn with_updater_config(mut self, updater:&ItemImportExportUpdater) -> Self {
+		self.test_file_suffix = updater.test_file_suffix().to_string();
+		self
+	}
+
+
+
+	/* USAGE METHODS */
+
+	/// Find all library usages in the crate. Usages in `src` are normal unless they are inside a unit test file or a `#[cfg(test)]` item, usages in `tests`, `benches` and `examples` are test-only and usages in the build script are build-only. Names of modules are only ignored in the file declaring them, so a local module does not hide a library with the same name elsewhere. Each library is reported once per context, with the file and span of its first usage. Files in each of these directories are scanned in path order, so the first usage does not depend on how the file system lists files.
+	pub fn scan(&self) -> Result<Vec<LibraryUsage>, Error> {
+		let mut files:Vec<(FileRef, UsageContext)> = Vec::new();
+		for file in rust_files(&FileRef::new(&format!("{}/{SOURCE_DIR}", self.crate_dir))) {
+			let context:UsageContext = if is_test_file(&file, &self.test_file_suffix) { UsageContext::Test } else { UsageContext::Normal };
+			files.push((file, context));
+		}
+		for test_dir in TEST_DIRS {
+			files.extend(rust_files(&FileRef::new(&format!("{}/{test_dir}", self.crate_dir))).into_iter().map(|file| (file, UsageContext::Test)));
+		}
+		let build_script:FileRef = FileRef::new(&format!("{}/{BUILD_SCRIPT_FILE}", self.crate_dir));
+		if build_script.exists() {
+			files.push((build_script, UsageContext::Build));
+		}
+
+		// Scan files. Module names are resolved per file, as a module is only in scope in the file declaring it.
+		let mut usages:Vec<LibraryUsage> = Vec::new();
+		for (file, context) in files {
+			let file_contents:String = file.read().map_err(|error| Error::io(file.path(), error))?;
+			for usage in library_usages(&file_contents, context) {
+				if !usages.contains(&usage) {
+					usages.push(usage.with_file(file.path()));
+				}
+			}
+		}
+		Ok(usages)
+	}
+}
+
+
+
+/// Find all library usages in the contents of a Rust file. Libraries are found from the roots of use declarations, extern crates and the first segment of qualified paths, which includes attribute paths like `#[tokio::main]` and macro invocations like `serde_json::json!`. Modules declared in the file, names imported by use declarations, primitives, capitalized names and the standard library are never reported. Usages inside `#[cfg(test)]` items are reported as test usages, unless the context is already more specific. Each usage has the span of its first occurrence.
+pub fn library_usages(contents:&str, context:UsageContext) -> Vec<LibraryUsage> {
+	let tokens:Vec<Token> = significant_tokens(contents);
+	let token_text = |index:usize| tokens.get(index).map(|token:&Token| &contents[token.start..token.end]).unwrap_or("");
+	let is_identifier = |index:usize| tokens.get(index).is_some_and(|token| token.kind == TokenKind::Identifier);
+	let is_path_separator = |index:usize| token_text(index) == ":" && token_text(index + 1) == ":";
+	let is_inner_segment = |index:usize| is_path_separator(index.wrapping_sub(2)) && (is_identifier(index.wrapping_sub(3)) || token_text(index.wrapping_sub(3)) == ">");
+	let module_names:Vec<String> = declared_module_names(contents);
+	let is_module_name = |name:&str| module_names.iter().any(|module_name| module_name == name);
+	let imported_names:Vec<&str> = imported_names(&token_text, tokens.len());
+	let line_index:LineIndex = LineIndex::new(contents);
+
+	let mut usages:Vec<LibraryUsage> = Vec::new();
+	let mut test_item_end:Option<usize> = None;
+	let mut token_index:usize = 0;
+	while token_index < tokens.len() {
+		if test_item_end.is_some_and(|end| token_index >= end) {
+			test_item_end = None;
+		}
+		let token_context:UsageContext = if test_item_end.is_some() && context == UsageContext::Normal { UsageContext::Test } else { context };
+
+		// Test-only items.
+		if test_item_end.is_none() && token_text(token_index) == "#" && is_cfg_test_attribute(&token_text, token_index + 1) {
+			if token_text(token_index + 1) == "!" {
+				test_item_end = Some(tokens.len());
+			} else {
+				test_item_end = Some(item_end(&token_text, token_index + 7));
+			}
+			token_index += 1;
+			continue;
+		}
+
+		// Find library names.
+		let mut name_indices:Vec<usize> = Vec::new();
+		match token_text(token_index) {
+			"use" => {
+				let statement_end:usize = (token_index..tokens.len()).find(|index| token_text(*index) == ";").unwrap_or(tokens.len());
+				name_indices.extend(use_tree_roots(&token_text, token_index + 1, statement_end).into_iter().filter(|index| is_identifier(*index) && !is_module_name(token_text(*index))));
+				token_index = statement_end;
+			},
+			"extern" if token_text(token_index + 1) == "crate" && is_identifier(token_index + 2) => name_indices.push(token_index + 2),
+			name if is_identifier(token_index) && is_path_separator(token_index + 1) && token_text(token_index + 3) != "<" && !is_inner_segment(token_index) && token_text(token_index.wrapping_sub(1)) != "." && !is_module_name(name) && !imported_names.contains(&name) => name_indices.push(token_index),
+			_ => {}
+		}
+		for name_index in name_indices {
+			let name:&str = token_text(name_index).trim_start_matches("r#");
+			if is_library_name(name) && !usages.iter().any(|usage| usage.name == name && usage.context == token_context) {
+				usages.push(LibraryUsage::new(name, token_context).with_span(line_index.span(contents, tokens[name_index].start, tokens[name_index].end)));
+			}
+		}
+		token_index += 1;
+	}
+	usages
+}
+
+/// Get all tokens of the contents of a Rust file, except whitespace and comments.
+fn significant_tokens(contents:&str) -> Vec<Token> {
+	RustLexer::new(contents).filter(|token| token.kind != TokenKind::Whitespace && token.kind != TokenKind::Comment).collect()
+}
+
+/// Find the names of all modules declared in the contents of a Rust file.
+fn declared_module_names(contents:&str) -> Vec<String> {
+	let tokens:Vec<Token> = significant_tokens(contents);
+	tokens.windows(2)
+		.filter(|pair| &contents[pair[0].start..pair[0].end] == "mod" && pair[1].kind == TokenKind::Identifier)
+		.map(|pair| contents[pair[1].start..pair[1].end].to_string())
+		.collect()
+}
+
+/// Whether or not a name could be the name of a library. Excludes the standard library, path keywords, primitives and capitalized names like types and `Self`.
+fn is_library_name(name:&str) -> bool {
+	const NON_LIBRARY_NAMES:&[&str] = &[
+		"std", "core", "alloc", "proc_macro", "test", "crate", "self", "super",
+		"bool", "char", "str", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32", "f64"
+	];
+	!name.is_empty() && !name.starts_with(|char:char| char.is_uppercase()) && !NON_LIBRARY_NAMES.contains(&name)
+}
+
+/// Find the token indices of the roots of the use tree between the given indices. A tree starting with a brace group has a root for each item in the group.
+fn use_tree_roots<'a, T:Fn(usize) -> &'a str>(token_text:&T, start:usize, end:usize) -> Vec<usize> {
+	let start:usize = if token_text(start) == ":" && token_text(start + 1) == ":" { start + 2 } else { start };
+	if token_text(start) != "{" {
+		return vec![start];
+	}
+	let mut roots:Vec<usize> = Vec::new();
+	let mut depth:usize = 0;
+	let mut expects_root:bool = true;
+	for index in start..end {
+		match token_text(index) {
+			"{" => {
+				depth += 1;
+				expects_root = depth == 1;
+			},
+			"}" => depth = depth.saturating_sub(1),
+			"," if depth == 1 => expects_root = true,
+			":" => {},
+			_ if expects_root => {
+				roots.push(index);
+				expects_root = false;
+			},
+			_ => {}
+		}
+	}
+	roots
+}
+
+/// Find all names brought into scope by use declarations, which are the last segments or aliases of the imported paths. A `self` import brings the name of the path before its brace group into scope.
+fn imported_names<'a, T:Fn(usize) -> &'a str>(token_text:&T, token_count:usize) -> Vec<&'a str> {
+	let mut names:Vec<&'a str> = Vec::new();
+	let mut use_start:Option<usize> = None;
+	for index in 0..token_count {
+		match token_text(index) {
+			"use" => use_start = Some(index),
+			";" => use_start = None,
+			"self" if use_start.is_some() && matches!(token_text(index + 1), "," | "}") => {
+				let mut depth:usize = 0;
+				let group_start:Option<usize> = (use_start.unwrap()..index).rev().find(|group_index| match token_text(*group_index) {
+					"}" => {
+						depth += 1;
+						false
+					},
+					"{" if depth == 0 => true,
+					"{" => {
+						depth -= 1;
+						false
+					},
+					_ => false
+				});
+				if let Some(group_start) = group_start.filter(|group_start| *group_start >= 3) {
+					names.push(token_text(group_start - 3));
+				}
+			},
+			name if use_start.is_some() && matches!(token_text(index + 1), "," | "}" | ";") && !matches!(name, "self" | "*" | "}" | "{") => names.push(name),
+			_ => {}
+		}
+	}
+	names
+}
+
+/// Whether or not the tokens starting at the given index, directly after a `#`, form a `[cfg(test)]` or `![cfg(test)]` attribute.
+fn is_cfg_test_attribute<'a, T:Fn(usize) -> &'a str>(token_text:&T, index:usize) -> bool {
+	let index:usize = if token_text(index) == "!" { index + 1 } else { index };
+	["[", "cfg", "(", "test", ")", "]"].iter().enumerate().all(|(offset, expected)| token_text(index + offset) == *expected)
+}
+
+/// Find the index of the token after the item starting at the given index. The item ends at the first semicolon or closed brace block at its own nesting level.
+fn item_end<'a, T:Fn(usize) -> &'a str>(token_text:&T, start:usize) -> usize {
+	let mut depth:usize = 0;
+	let mut index:usize = start;
+	loop {
+		match token_text(index) {
+			"" => return index,
+			"{" | "(" | "[" => depth += 1,
+			"}" | ")" | "]" => {
+				depth = depth.saturating_sub(1);
+				if depth == 0 && token_text(index) == "}" {
+					return index + 1;
+				}
+			},
+			";" if depth == 0 => return index + 1,
+			_ => {}
+		}
+		index += 1;
+	}
+}
+
+/// Find all Rust files in a directory and its sub-directories, ordered by path.
+fn rust_files(dir:&FileRef) -> Vec<FileRef> {
+	if !dir.exists() {
+		return Vec::new();
+	}
+	let mut files:Vec<FileRef> = dir.scanner().include_files().filter(|file| file.extension() == Some("rs")).collect();
+	for sub_dir in dir.list_dirs() {
+		files.extend(rust_files(&sub_dir));
+	}
+	files.sort_by(|a, b| a.path().cmp(b.path()));
+	files
+}
\ No newline at end of file
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ library_usages, ItemImportExportUpdater, LibraryUsage, LibraryUsageScanner, Span, UsageContext };
+
+
+	#[test]
+	fn test_use_and_extern_crate() {
+		let usages:Vec<LibraryUsage> = library_usages("use file_ref::FileRef;\npub use ::glyph_kit::{ TextMatcher };\nextern crate cachew;\n// use commented::Out;\nconst TEXT:&str = \"use quoted::Out;\";\n", UsageContext::Normal);
+		assert_eq!(usages, vec![
+			LibraryUsage::new("file_ref", UsageContext::Normal),
+			LibraryUsage::new("glyph_kit", UsageContext::Normal),
+			LibraryUsage::new("cachew", UsageContext::Normal)
+		]);
+	}
+
+	#[test]
+	fn test_usage_spans() {
+		let contents:&str = "use first::A;\nfn run() {\n\tlet value = second::call(first::other());\n}\n";
+		let usages:Vec<LibraryUsage> = library_usages(contents, UsageContext::Normal);
+		let locations:Vec<(&str, usize, usize, &str)> = usages.iter().map(|usage| {
+			let span:&Span = usage.span.as_ref().unwrap();
+			(usage.name.as_str(), span.line, span.column, &contents[span.start..span.end])
+		}).collect();
+		assert_eq!(locations, vec![("first", 1, 5, "first"), ("second", 3, 14, "second")]);
+	}
+
+	#[test]
+	fn test_cfg_test_items() {
+		let contents:&str = "use normal::A;\n#[cfg(test)]\nmod tests {\n\tuse tested::B;\n\tfn nested() { {} }\n\tuse also_tested::C;\n}\n#[cfg(test)]\nuse single_item::D;\nuse after::E;\n";
+		let usages:Vec<LibraryUsage> = library_usages(contents, UsageContext::Normal);
+		assert_eq!(usages, vec![
+			LibraryUsage::new("normal", UsageContext::Normal),
+			LibraryUsage::new("tested", UsageContext::Test),
+			LibraryUsage::new("also_tested", UsageContext::Test),
+			LibraryUsage::new("single_item", UsageContext::Test),
+			LibraryUsage::new("after", UsageContext::Normal)
+		]);
+
+		let usages:Vec<LibraryUsage> = library_usages("#![cfg(test)]\nuse tested::A;\n", UsageContext::Normal);
+		assert_eq!(usages, vec![LibraryUsage::new("tested", UsageContext::Test)]);
+
+		let usages:Vec<LibraryUsage> = library_usages("#[cfg(test)]\nuse build_tool::A;\n", UsageContext::Build);
+		assert_eq!(usages, vec![LibraryUsage::new("build_tool", UsageContext::Build)]);
+	}
+
+	#[test]
+	fn test_qualified_paths_attributes_and_macros() {
+		let contents:&str = "#[tokio::main]\nasync fn main() {\n\tlet text:String = serde_json::to_string(&value).unwrap();\n\tlet map = ::indexmap::IndexMap::new();\n\tlog::info!(\"{}\", text);\n}\n#[derive(Debug, serde::Serialize)]\nstruct Data;\n";
+		let names:Vec<String> = library_usages(contents, UsageContext::Normal).into_iter().map(|usage| usage.name).collect();
+		assert_eq!(names, vec!["tokio", "serde_json", "indexmap", "log", "serde"]);
+	}
+
+	#[test]
+	fn test_non_library_paths_are_ignored() {
+		let contents:&str = "mod local;\nuse std::{ fmt, io::{ self, Read } };\nuse other_crate::module as alias;\nfn run() {\n\tlocal::run();\n\tfmt::format(args);\n\tio::stdin();\n\talias::call();\n\tstd::mem::drop(x);\n\tcore::mem::drop(x);\n\tcrate::run();\n\tSelf::new();\n\tString::new();\n\tu32::MAX;\n\tvalue.parse::<u32>();\n\tgeneric::<u32>();\n\tlet path = \"quoted::path\";\n}\n";
+		let names:Vec<String> = library_usages(contents, UsageContext::Normal).into_iter().map(|usage| usage.name).collect();
+		assert_eq!(names, vec!["other_crate"]);
+	}
+
+	#[test]
+	fn test_use_tree_roots() {
+		let names:Vec<String> = library_usages("use { first::A, second::{ B, c::D } };\nuse third;\n", UsageContext::Normal).into_iter().map(|usage| usage.name).collect();
+		assert_eq!(names, vec!["first", "second", "third"]);
+	}
+
+	#[test]
+	fn test_scan_crate_directories() {
+		const CRATE_DIR:&str = "target/crate_manager/test_usage_scan";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		for dir in ["src/nested", "tests", "benches"] {
+			std::fs::create_dir_all(format!("{CRATE_DIR}/{dir}")).unwrap();
+		}
+		std::fs::write(format!("{CRATE_DIR}/src/lib.rs"), "mod nested;\nuse normal::A;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/nested/mod.rs"), "mod file_u;\nmod helpers;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/nested/helpers.rs"), "use super::file_u;\nfn run() { self::run(); }\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/nested/file_u.rs"), "use unit_test::A;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/tests/integration.rs"), "use integration_test::A;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/benches/bench.rs"), "use bench::A;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/build.rs"), "use build_tool::A;\n").unwrap();
+
+		let mut usages:Vec<LibraryUsage> = LibraryUsageScanner::new(CRATE_DIR).scan().unwrap();
+		usages.sort_by(|a, b| a.name.cmp(&b.name));
+		assert_eq!(usages, vec![
+			LibraryUsage::new("bench", UsageContext::Test),
+			LibraryUsage::new("build_tool", UsageContext::Build),
+			LibraryUsage::new("integration_test", UsageContext::Test),
+			LibraryUsage::new("normal", UsageContext::Normal),
+			LibraryUsage::new("unit_test", UsageContext::Test)
+		]);
+		assert!(usages[3].file.ends_with("src/lib.rs"));
+		assert_eq!(usages[3].span.as_ref().map(|span| (span.line, span.column)), Some((2, 5)));
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_scan_uses_updater_test_file_suffix() {
+		const CRATE_DIR:&str = "target/crate_manager/test_usage_scan_suffix";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(format!("{CRATE_DIR}/src")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/lib.rs"), "mod helpers;\nmod helpers_test;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/helpers.rs"), "use normal::A;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/helpers_test.rs"), "use unit_test::A;\n").unwrap();
+
+		// The scanner treats the same files as test files as the updater does.
+		let updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&format!("{CRATE_DIR}/src/lib.rs")).with_test_file_suffix("_test");
+		let mut usages:Vec<LibraryUsage> = LibraryUsageScanner::new(CRATE_DIR).with_updater_config(&updater).scan().unwrap();
+		usages.sort_by(|a, b| a.name.cmp(&b.name));
+		assert_eq!(usages, vec![LibraryUsage::new("normal", UsageContext::Normal), LibraryUsage::new("unit_test", UsageContext::Test)]);
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_first_usage_in_path_order_is_kept() {
+		const CRATE_DIR:&str = "target/crate_manager/test_usage_scan_order";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(format!("{CRATE_DIR}/src/nested")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/lib.rs"), "mod b;\nmod a;\nmod nested;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/b.rs"), "use shared::B;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/nested/mod.rs"), "use shared::N;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/a.rs"), "\nuse shared::A;\n").unwrap();
+
+		// Only the usage in the first file by path is reported.
+		let usages:Vec<LibraryUsage> = LibraryUsageScanner::new(CRATE_DIR).scan().unwrap();
+		assert_eq!(usages, vec![LibraryUsage::new("shared", UsageContext::Normal)]);
+		assert!(usages[0].file.ends_with("src/a.rs"));
+		assert_eq!(usages[0].span.as_ref().map(|span| span.line), Some(2));
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_module_names_are_scoped_to_their_file() {
+		const CRATE_DIR:&str = "target/crate_manager/test_usage_scan_scope";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(format!("{CRATE_DIR}/src/features")).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/lib.rs"), "mod features;\nmod encoding;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/features/mod.rs"), "mod serde;\npub use serde::*;\nfn local() { serde::local(); }\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/features/serde.rs"), "use serde::Serialize;\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/src/encoding.rs"), "fn encode() { serde_json::to_string(&serde::de::IgnoredAny).unwrap(); }\n").unwrap();
+
+		// The nested `serde` module only shadows the library inside the file declaring it.
+		let mut usages:Vec<LibraryUsage> = LibraryUsageScanner::new(CRATE_DIR).scan().unwrap();
+		usages.sort_by(|a, b| a.name.cmp(&b.name));
+		assert_eq!(usages, vec![LibraryUsage::new("serde", UsageContext::Normal), LibraryUsage::new("serde_json", UsageContext::Normal)]);
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+}
\ No newline at end of file
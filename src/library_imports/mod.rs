@@ -1,8 +1,27 @@
 // auto-exports
+#[cfg(test)]
+mod library_imports_updater_u;
 mod library_imports_updater;
+#[cfg(test)]
+mod library_usage_scanner_u;
+mod library_usage_scanner;
+#[cfg(test)]
 mod libraries_storage_u;
 mod libraries_storage;
+#[cfg(test)]
+mod toml_document_u;
+mod toml_document;
+#[cfg(test)]
+mod toml_reader_u;
+mod toml_reader;
+#[cfg(test)]
+mod semver_u;
+mod semver;
 
-pub use library_imports_updater::*; // generate_toml_imports
-
-pub use libraries_storage::*; // LibrariesStorage, Library
\ No newline at end of file
+pub use library_imports_updater::*; // TomlImportOptions, generate_toml_imports, generate_toml_imports_with_options
+pub use library_usage_scanner::*; // UsageContext, LibraryUsage, LibraryUsageScanner, library_usages
+pub use libraries_storage::*; // LibrariesStorage, GitReference, Library, parse_git_url
+pub(crate) use libraries_storage::*; // canonical_crate_name
+pub use toml_document::*; // TomlDocument
+pub use toml_reader::*; // TomlValue, TomlStatement, read_toml, statement_span, read_toml_statements
+pub use semver::*; // Version, VersionOperator, VersionComparator, VersionReq, VersionSelection
\ No newline at end of file
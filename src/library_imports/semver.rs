@@ -0,0 +1,300 @@
+use std::{ cmp::Ordering, fmt::{ self, Display, Formatter } };
+
+
+
+#[derive(Debug, Clone)]
+pub struct Version {
+	pub major:u64,
+	pub minor:u64,
+	pub patch:u64,
+	pub pre:String,
+	pub build:String
+}
+impl Version {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new version without pre-release or build metadata.
+	pub fn new(major:u64, minor:u64, patch:u64) -> Version {
+		Version { major, minor, patch, pre: String::new(), build: String::new() }
+	}
+
+	/// Parse a full `major.minor.patch` version with optional `-pre` and `+build` suffixes.
+	pub fn parse(text:&str) -> Option<Version> {
+		let (version, build) = split_suffix(text.trim(), '+');
+		let (version, pre) = split_suffix(version, '-');
+		let numbers:Vec<u64> = version.split('.').map(parse_number).collect::<Option<Vec<u64>>>()?;
+		if numbers.len() != 3 || !is_valid_suffix(pre) || !is_valid_suffix(build) {
+			return None;
+		}
+		Some(Version { major: numbers[0], minor: numbers[1], patch: numbers[2], pre: pre.unwrap_or_default().to_string(), build: build.unwrap_or_default().to_string() })
+	}
+
+
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Whether or not this is a pre-release version.
+	pub fn is_pre_release(&self) -> bool {
+		!self.pre.is_empty()
+	}
+}
+impl PartialEq for Version {
+
+	/// Versions are equal when they have the same precedence. Like the ordering, this ignores build metadata.
+	fn eq(&self, other:&Version) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+impl Eq for Version {}
+impl Ord for Version {
+	fn cmp(&self, other:&Version) -> Ordering {
+		(self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch)).then_with(|| compare_pre_releases(&self.pre, &other.pre))
+	}
+}
+impl PartialOrd for Version {
+	fn partial_cmp(&self, other:&Version) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl Display for Version {
+	fn fmt(&self, f:&mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+		if !self.pre.is_empty() {
+			write!(f, "-{}", self.pre)?;
+		}
+		if !self.build.is_empty() {
+			write!(f, "+{}", self.build)?;
+		}
+		Ok(())
+	}
+}
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VersionOperator { Caret, Tilde, Exact, Greater, GreaterEq, Less, LessEq, Wildcard }
+impl VersionOperator {
+
+	/// Get the symbol of the operator as written in a requirement. Caret is the default operator and is written without symbol.
+	pub fn symbol(&self) -> &'static str {
+		match self {
+			VersionOperator::Caret | VersionOperator::Wildcard => "",
+			VersionOperator::Tilde => "~",
+			VersionOperator::Exact => "=",
+			VersionOperator::Greater => ">",
+			VersionOperator::GreaterEq => ">=",
+			VersionOperator::Less => "<",
+			VersionOperator::LessEq => "<="
+		}
+	}
+}
+
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionComparator {
+	pub operator:VersionOperator,
+	pub major:Option<u64>,
+	pub minor:Option<u64>,
+	pub patch:Option<u64>,
+	pub pre:String
+}
+impl VersionComparator {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Parse a single comparator like `^1.2`, `>=0.3.0-alpha.1`, `1.*` or `*`.
+	fn parse(text:&str) -> Option<VersionComparator> {
+		let text:&str = text.trim();
+		let (operator, version) = [
+			(">=", VersionOperator::GreaterEq), ("<=", VersionOperator::LessEq), (">", VersionOperator::Greater), ("<", VersionOperator::Less),
+			("=", VersionOperator::Exact), ("~", VersionOperator::Tilde), ("^", VersionOperator::Caret)
+		].into_iter().find_map(|(symbol, operator)| text.strip_prefix(symbol).map(|version| (operator, version.trim()))).unwrap_or((VersionOperator::Caret, text));
+		let (version, build) = split_suffix(version, '+');
+		let (version, pre) = split_suffix(version, '-');
+		if version.is_empty() || !is_valid_suffix(pre) || !is_valid_suffix(build) {
+			return None;
+		}
+		let pre:&str = pre.unwrap_or_default();
+
+		// Parse numbers, where a wildcard ends the version.
+		let mut numbers:Vec<Option<u64>> = Vec::new();
+		let mut has_wildcard:bool = false;
+		for part in version.split('.') {
+			if has_wildcard || numbers.len() == 3 {
+				return None;
+			}
+			if matches!(part, "*" | "x" | "X") {
+				has_wildcard = true;
+			} else {
+				numbers.push(Some(parse_number(part)?));
+			}
+		}
+		if has_wildcard && (operator != VersionOperator::Caret || !pre.is_empty()) {
+			return None;
+		}
+		numbers.resize(3, None);
+		Some(VersionComparator {
+			operator: if has_wildcard { VersionOperator::Wildcard } else { operator },
+			major: numbers[0],
+			minor: numbers[1],
+			patch: numbers[2],
+			pre: pre.to_string()
+		})
+	}
+
+
+
+	/* USAGE METHODS */
+
+	/// Get the lowest version this comparator is based on, with missing numbers as zero.
+	fn base_version(&self) -> Version {
+		Version { pre: self.pre.clone(), ..Version::new(self.major.unwrap_or(0), self.minor.unwrap_or(0), self.patch.unwrap_or(0)) }
+	}
+
+	/// Whether or not the version matches this comparator, ignoring pre-release restrictions.
+	fn matches(&self, version:&Version) -> bool {
+		let base:Version = self.base_version();
+		let partial_matches = || self.major.is_none_or(|major| major == version.major) && self.minor.is_none_or(|minor| minor == version.minor) && self.patch.is_none_or(|patch| patch == version.patch);
+		match self.operator {
+			VersionOperator::Exact => if self.patch.is_some() { *version == base } else { partial_matches() },
+			VersionOperator::Wildcard => partial_matches(),
+			VersionOperator::Greater => if self.patch.is_some() { *version > base } else { *version > base && !partial_matches() },
+			VersionOperator::GreaterEq => *version >= base,
+			VersionOperator::Less => *version < base,
+			VersionOperator::LessEq => if self.patch.is_some() { *version <= base } else { *version < base || partial_matches() },
+			VersionOperator::Tilde => *version >= base && version.major == base.major && (self.minor.is_none() || version.minor == base.minor),
+			VersionOperator::Caret => *version >= base && match (self.major, self.minor, self.patch) {
+				(Some(0), Some(0), Some(_)) => version.major == 0 && version.minor == 0 && version.patch == base.patch,
+				(Some(0), Some(minor), _) => version.major == 0 && version.minor == minor,
+				_ => version.major == base.major
+			}
+		}
+	}
+}
+impl Display for VersionComparator {
+	fn fmt(&self, f:&mut Formatter<'_>) -> fmt::Result {
+		let numbers:Vec<String> = [self.major, self.minor, self.patch].into_iter().map_while(|number| number.map(|number| number.to_string())).collect();
+		write!(f, "{}{}", self.operator.symbol(), numbers.join("."))?;
+		if self.operator == VersionOperator::Wildcard {
+			write!(f, "{}*", if numbers.is_empty() { "" } else { "." })?;
+		}
+		if !self.pre.is_empty() {
+			write!(f, "-{}", self.pre)?;
+		}
+		Ok(())
+	}
+}
+
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionReq {
+	pub comparators:Vec<VersionComparator>
+}
+impl VersionReq {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Parse a version requirement like `1.0`, `^1.2`, `~0.3.0-alpha.1`, `1.*` or `>=1, <2`.
+	pub fn parse(text:&str) -> Option<VersionReq> {
+		let comparators:Vec<VersionComparator> = text.split(',').map(VersionComparator::parse).collect::<Option<Vec<VersionComparator>>>()?;
+		Some(VersionReq { comparators })
+	}
+
+	/// Create a requirement that only matches the exact version.
+	pub fn exact(version:&Version) -> VersionReq {
+		VersionReq {
+			comparators: vec![VersionComparator { operator: VersionOperator::Exact, major: Some(version.major), minor: Some(version.minor), patch: Some(version.patch), pre: version.pre.clone() }]
+		}
+	}
+
+
+
+	/* USAGE METHODS */
+
+	/// Whether or not the version matches the requirement. Like Cargo, pre-release versions only match if a comparator explicitly mentions a pre-release of the same version.
+	pub fn matches(&self, version:&Version) -> bool {
+		self.comparators.iter().all(|comparator| comparator.matches(version)) && (!version.is_pre_release() || self.comparators.iter().any(|comparator| {
+			!comparator.pre.is_empty() && comparator.major == Some(version.major) && comparator.minor == Some(version.minor) && comparator.patch == Some(version.patch)
+		}))
+	}
+
+	/// Get the lowest version the requirement is based on. Used to order requirements.
+	pub fn minimum_version(&self) -> Version {
+		self.comparators.iter().filter(|comparator| !matches!(comparator.operator, VersionOperator::Less | VersionOperator::LessEq)).map(|comparator| comparator.base_version()).max().unwrap_or(Version::new(0, 0, 0))
+	}
+}
+impl Display for VersionReq {
+	fn fmt(&self, f:&mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.comparators.iter().map(|comparator| comparator.to_string()).collect::<Vec<String>>().join(", "))
+	}
+}
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum VersionSelection {
+
+	/// Use the highest known version.
+	#[default]
+	Highest,
+
+	/// Use the highest known version compatible with a requirement already in the manifest, falling back to the highest version.
+	Compatible,
+
+	/// Use the highest known version, pinned to exactly that version.
+	Pinned
+}
+
+
+
+/// Split text at the first occurrence of the separator. The suffix is none if the separator is not found.
+fn split_suffix(text:&str, separator:char) -> (&str, Option<&str>) {
+	match text.split_once(separator) {
+		Some((text, suffix)) => (text, Some(suffix)),
+		None => (text, None)
+	}
+}
+
+/// Whether or not the suffix is a valid list of dot-separated pre-release or build identifiers. A missing suffix is valid.
+fn is_valid_suffix(suffix:Option<&str>) -> bool {
+	suffix.is_none_or(|text| text.split('.').all(|identifier| !identifier.is_empty() && identifier.chars().all(|char| char.is_ascii_alphanumeric() || char == '-')))
+}
+
+/// Parse a version number without leading zeros.
+fn parse_number(text:&str) -> Option<u64> {
+	if text.is_empty() || !text.chars().all(|char| char.is_ascii_digit()) || (text.len() > 1 && text.starts_with('0')) {
+		return None;
+	}
+	text.parse::<u64>().ok()
+}
+
+/// Compare pre-release identifiers by semver precedence. A version without pre-release has higher precedence, numeric identifiers are compared numerically and have lower precedence than alphanumeric ones.
+fn compare_pre_releases(pre:&str, other_pre:&str) -> Ordering {
+	match (pre.is_empty(), other_pre.is_empty()) {
+		(true, true) => return Ordering::Equal,
+		(true, false) => return Ordering::Greater,
+		(false, true) => return Ordering::Less,
+		(false, false) => {}
+	}
+	let mut identifiers = pre.split('.');
+	let mut other_identifiers = other_pre.split('.');
+	loop {
+		let ordering:Ordering = match (identifiers.next(), other_identifiers.next()) {
+			(None, None) => return Ordering::Equal,
+			(None, Some(_)) => return Ordering::Less,
+			(Some(_), None) => return Ordering::Greater,
+			(Some(identifier), Some(other_identifier)) => match (identifier.parse::<u64>(), other_identifier.parse::<u64>()) {
+				(Ok(number), Ok(other_number)) => number.cmp(&other_number),
+				(Ok(_), Err(_)) => Ordering::Less,
+				(Err(_), Ok(_)) => Ordering::Greater,
+				(Err(_), Err(_)) => identifier.cmp(other_identifier)
+			}
+		};
+		if ordering != Ordering::Equal {
+			return ordering;
+		}
+	}
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ Version, VersionReq };
+
+
+	fn version(text:&str) -> Version {
+		Version::parse(text).unwrap()
+	}
+
+	fn matches(requirement:&str, text:&str) -> bool {
+		VersionReq::parse(requirement).unwrap().matches(&version(text))
+	}
+
+	#[test]
+	fn test_version_parsing() {
+		assert_eq!(version("1.2.3"), Version::new(1, 2, 3));
+		assert_eq!(version("0.3.0-alpha.1").pre, "alpha.1");
+		assert_eq!(version("1.0.0+build.5").build, "build.5");
+		assert_eq!(version("0.3.0-alpha.1+build").to_string(), "0.3.0-alpha.1+build");
+		assert!(Version::parse("1.0").is_none());
+		assert!(Version::parse("01.0.0").is_none());
+		assert!(Version::parse("1.0.0-").is_none());
+		assert!(Version::parse("Cargo.toml").is_none());
+	}
+
+	#[test]
+	fn test_version_ordering() {
+		let ordered:Vec<Version> = ["1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta.2", "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0", "1.2.0", "1.10.0"].iter().map(|text| version(text)).collect();
+		assert!(ordered.windows(2).all(|pair| pair[0] < pair[1]));
+
+		// Build metadata does not affect precedence or equality.
+		assert_eq!(version("1.0.0+a").cmp(&version("1.0.0+b")), std::cmp::Ordering::Equal);
+		assert_eq!(version("1.0.0+a"), version("1.0.0+b"));
+		let mut versions:Vec<Version> = vec![version("1.0.0+b"), version("1.0.0+a"), version("0.9.0")];
+		versions.sort();
+		versions.dedup();
+		assert_eq!(versions.len(), 2);
+	}
+
+	#[test]
+	fn test_requirement_parsing() {
+		assert_eq!(VersionReq::parse("1.0").unwrap().to_string(), "1.0");
+		assert_eq!(VersionReq::parse("^1.2").unwrap().to_string(), "1.2");
+		assert_eq!(VersionReq::parse(">=1, <2").unwrap().to_string(), ">=1, <2");
+		assert_eq!(VersionReq::parse("~ 0.3.0-alpha.1").unwrap().to_string(), "~0.3.0-alpha.1");
+		assert_eq!(VersionReq::parse("1.*").unwrap().to_string(), "1.*");
+		assert_eq!(VersionReq::parse(">=1, <2").unwrap().minimum_version(), Version::new(1, 0, 0));
+		assert!(VersionReq::parse("").is_none());
+		assert!(VersionReq::parse("1.2.3.4").is_none());
+		assert!(VersionReq::parse(">=1.*").is_none());
+		assert!(VersionReq::parse("https://github.com/lib").is_none());
+	}
+
+	#[test]
+	fn test_requirement_matching() {
+		assert!(matches("1.2", "1.9.0"));
+		assert!(!matches("1.2", "2.0.0"));
+		assert!(!matches("1.2", "1.1.9"));
+		assert!(matches("0.2.3", "0.2.9"));
+		assert!(!matches("0.2.3", "0.3.0"));
+		assert!(!matches("0.0.3", "0.0.4"));
+		assert!(matches("~1.2", "1.2.9"));
+		assert!(!matches("~1.2", "1.3.0"));
+		assert!(matches("=1.2", "1.2.5"));
+		assert!(matches(">=1, <2", "1.5.0"));
+		assert!(!matches(">=1, <2", "2.0.0"));
+		assert!(matches("<=1.2", "1.2.7"));
+		assert!(!matches(">1.2", "1.2.7"));
+		assert!(matches("*", "3.1.4"));
+
+		// Pre-releases only match requirements mentioning a pre-release of the same version.
+		assert!(!matches("1.0", "1.1.0-alpha.1"));
+		assert!(matches("0.3.0-alpha.1", "0.3.0-alpha.2"));
+		assert!(!matches("0.3.0-alpha.1", "0.3.0-alpha.0"));
+	}
+}
\ No newline at end of file
@@ -0,0 +1,317 @@
+use crate::{ Error, read_toml_statements, Span, statement_span, TomlStatement, TomlValue };
+
+
+
+const DEFAULT_KEY_SEPARATOR:&str = " = ";
+
+
+
+struct TomlEntry {
+	table:Vec<String>,
+	keys:Vec<String>,
+	is_header:bool,
+	value:String,
+	first_line:usize,
+	last_line:usize
+}
+impl TomlEntry {
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Get the keys of the entry relative to the given table, or none if the entry is not part of the table. For a `[table.key]` header the relative keys are the path below the table.
+	fn keys_in(&self, table:&str) -> Option<Vec<String>> {
+		relative_keys(&self.table.iter().chain(&self.keys).cloned().collect::<Vec<String>>(), table)
+	}
+
+	/// Whether or not this is a key-value pair defined directly in the given table.
+	fn is_pair_in(&self, table:&str) -> bool {
+		!self.is_header && self.table.join(".") == table
+	}
+}
+
+
+
+pub struct TomlDocument {
+	path:String,
+	lines:Vec<String>,
+	uses_crlf:bool,
+	value:TomlValue,
+	statements:Vec<TomlStatement>,
+	entries:Vec<TomlEntry>
+}
+impl TomlDocument {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new document from the contents of a TOML file. The contents are stored as they are, so unchanged parts are written back byte-identical. Uses the same reader as `read_toml`, so keys the document finds and edits are the keys the manifest defines.
+	pub fn parse(path:&str, contents:&str) -> Result<TomlDocument, Error> {
+		let (value, statements) = read_toml_statements(path, contents)?;
+		Ok(TomlDocument {
+			path: path.to_string(),
+			lines: contents.split('\n').map(|line| line.to_string()).collect(),
+			uses_crlf: contents.contains("\r\n"),
+			value,
+			entries: toml_entries(contents, &statements),
+			statements
+		})
+	}
+
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Get the full contents of the document.
+	pub fn contents(&self) -> String {
+		self.lines.join("\n")
+	}
+
+	/// Get the value of the document as read by `read_toml`.
+	pub fn value(&self) -> &TomlValue {
+		&self.value
+	}
+
+	/// Get the location of the statement defining the value at the path of keys, or of the closest statement defining part of it.
+	pub fn span(&self, keys:&[&str]) -> Option<Span> {
+		statement_span(&self.statements, keys)
+	}
+
+	/// Get the names of all tables in the document, in order of appearance.
+	pub fn tables(&self) -> Vec<String> {
+		let mut tables:Vec<String> = Vec::new();
+		for entry in self.entries.iter().filter(|entry| entry.is_header) {
+			let table:String = entry.table.join(".");
+			if !tables.contains(&table) {
+				tables.push(table);
+			}
+		}
+		tables
+	}
+
+	/// Get the keys in a table, in order of appearance. Keys defined through dotted keys or `[table.key]` sub-tables are included once.
+	pub fn keys(&self, table:&str) -> Vec<String> {
+		let mut keys:Vec<String> = Vec::new();
+		for key in self.entries.iter().filter_map(|entry| entry.keys_in(table)?.into_iter().next()) {
+			if !keys.contains(&key) {
+				keys.push(key);
+			}
+		}
+		keys
+	}
+
+	/// Get the raw value of a key in a table, without surrounding whitespace and comments. Keys defined through dotted keys or sub-tables have no single raw value.
+	pub fn get(&self, table:&str, key:&str) -> Option<String> {
+		self.entries.iter().find(|entry| !entry.is_header && entry.keys_in(table) == Some(vec![key.to_string()])).map(|entry| entry.value.clone())
+	}
+
+
+	/* USAGE METHODS */
+
+	/// Insert a key with a raw value into a table, adding exactly one line. If the keys of the table are sorted, the line is inserted in sorted position, otherwise after the last key. A missing table is added at the end of the document. Matches the spacing around the equals sign used by the rest of the document. Returns false if the key already exists or the result would not be valid TOML.
+	pub fn insert(&mut self, table:&str, key:&str, value:&str) -> bool {
+		if self.keys(table).iter().any(|existing_key| existing_key == key) {
+			return false;
+		}
+		let new_line:String = format!("{key}{}{value}", self.key_separator(table));
+		self.edit(|document| {
+			document.insert_entry_lines(table, key, &[new_line]);
+			true
+		})
+	}
+
+	/// Remove a key from a table, including all dotted keys and `[table.key]` sub-tables defining it. Returns false if the key was not found or the result would not be valid TOML.
+	pub fn remove(&mut self, table:&str, key:&str) -> bool {
+		let mut removed_lines:Vec<bool> = vec![false; self.lines.len()];
+		let mut removes_last_table:bool = false;
+		for (entry_index, entry) in self.entries.iter().enumerate().filter(|(_, entry)| entry.keys_in(table).is_some_and(|keys| keys.first().map(|first| first.as_str()) == Some(key))) {
+			let last_line:usize = if entry.is_header { self.table_end_line(entry_index) } else { entry.last_line };
+			removes_last_table |= entry.is_header && self.entries[entry_index + 1..].iter().all(|next_entry| !next_entry.is_header);
+			removed_lines[entry.first_line..=last_line].iter_mut().for_each(|removed| *removed = true);
+		}
+		if !removed_lines.contains(&true) {
+			return false;
+		}
+		self.edit(|document| {
+			document.lines = document.lines.drain(..).zip(&removed_lines).filter(|(_, removed)| !**removed).map(|(line, _)| line).collect();
+
+			// Without a following table, the blank lines separating the removed sub-table from the previous table are no longer needed.
+			if removes_last_table {
+				let end_index:usize = document.lines.len() - if document.lines.last().is_some_and(|line| line.is_empty()) { 1 } else { 0 };
+				let mut blank_start:usize = end_index;
+				while blank_start > 0 && document.lines[blank_start - 1].trim().is_empty() {
+					blank_start -= 1;
+				}
+				document.lines.drain(blank_start..end_index);
+			}
+			true
+		})
+	}
+
+	/// Move a key from one table to another, keeping its value and formatting. Dotted keys are moved together and `[from.key]` sub-tables are renamed to `[to.key]`. Returns false if the key was not found, is defined outside of the table, or the result would not be valid TOML.
+	pub fn move_key(&mut self, from_table:&str, to_table:&str, key:&str) -> bool {
+		let is_moved = |entry:&TomlEntry| entry.keys_in(from_table).is_some_and(|keys| keys.first().map(|first| first.as_str()) == Some(key));
+		let moved_entries:Vec<&TomlEntry> = self.entries.iter().filter(|entry| is_moved(entry)).collect();
+		let header_lines:Vec<usize> = moved_entries.iter().filter(|entry| entry.is_header).map(|entry| entry.first_line).collect();
+		let pair_lines:Vec<(usize, usize)> = moved_entries.iter().filter(|entry| entry.is_pair_in(from_table)).map(|entry| (entry.first_line, entry.last_line)).collect();
+
+		// Pairs in a moved sub-table move with its header, pairs defined anywhere else cannot be moved.
+		let is_movable = |entry:&&TomlEntry| entry.is_header || entry.is_pair_in(from_table) || relative_keys(&entry.table, from_table).is_some_and(|keys| keys.first().map(|first| first.as_str()) == Some(key));
+		if moved_entries.is_empty() || !moved_entries.iter().all(is_movable) {
+			return false;
+		}
+		self.edit(|document| {
+			for header_line in header_lines {
+				let header:String = document.lines[header_line].clone();
+				let name_start:usize = header.find(from_table).unwrap_or(0);
+				document.lines[header_line] = format!("{}{to_table}{}", &header[..name_start], &header[name_start + from_table.len()..]);
+			}
+			let mut entry_lines:Vec<String> = Vec::new();
+			for (first_line, last_line) in pair_lines.into_iter().rev() {
+				let lines:Vec<String> = document.lines.drain(first_line..=last_line).map(|line| line.trim_end_matches('\r').to_string()).collect();
+				entry_lines.splice(0..0, lines);
+			}
+			if !entry_lines.is_empty() {
+				if !document.reload() {
+					return false;
+				}
+				document.insert_entry_lines(to_table, key, &entry_lines);
+			}
+			true
+		})
+	}
+
+	/// Apply an edit to the lines of the document and read the result again. If the edit fails or the result is not valid TOML, the document is left unchanged. Returns whether or not the edit was applied.
+	fn edit<T:FnOnce(&mut TomlDocument) -> bool>(&mut self, edit:T) -> bool {
+		let original_lines:Vec<String> = self.lines.clone();
+		if edit(self) && self.reload() {
+			return true;
+		}
+		self.lines = original_lines;
+		self.reload();
+		false
+	}
+
+	/// Read the entries of the document again after its lines changed. Returns false if the lines are not valid TOML.
+	fn reload(&mut self) -> bool {
+		let contents:String = self.contents();
+		match read_toml_statements(&self.path, &contents) {
+			Ok((value, statements)) => {
+				self.entries = toml_entries(&contents, &statements);
+				self.statements = statements;
+				self.value = value;
+				true
+			},
+			Err(_) => false
+		}
+	}
+
+	/// Insert the lines of a key-value entry into a table. If the keys of the table are sorted, the lines are inserted in sorted position, otherwise after the last key. A missing table is added at the end of the document.
+	fn insert_entry_lines(&mut self, table:&str, key:&str, entry_lines:&[String]) {
+		let table_entries:Vec<&TomlEntry> = self.entries.iter().filter(|entry| entry.is_pair_in(table)).collect();
+
+		// Insert in existing table.
+		let sorted:bool = table_entries.windows(2).all(|pair| pair[0].keys[0] <= pair[1].keys[0]);
+		let insert_index:Option<usize> = if let Some(next_entry) = table_entries.iter().find(|entry| sorted && entry.keys[0].as_str() > key) {
+			Some(next_entry.first_line)
+		} else if let Some(last_entry) = table_entries.last() {
+			Some(last_entry.last_line + 1)
+		} else {
+			self.entries.iter().find(|entry| entry.is_header && entry.table.join(".") == table).map(|header| header.first_line + 1)
+		};
+		if let Some(insert_index) = insert_index {
+			for (offset, line) in entry_lines.iter().enumerate() {
+				self.insert_line(insert_index + offset, line);
+			}
+		}
+
+		// Add new table at the end, separated by a blank line.
+		else {
+			let mut end_index:usize = self.lines.len();
+			if self.lines.last().is_some_and(|line| line.is_empty()) {
+				end_index -= 1;
+			}
+			if end_index > 0 && !self.lines[end_index - 1].trim().is_empty() {
+				self.insert_line(end_index, "");
+				end_index += 1;
+			}
+			self.insert_line(end_index, &format!("[{table}]"));
+			for (offset, line) in entry_lines.iter().enumerate() {
+				self.insert_line(end_index + 1 + offset, line);
+			}
+		}
+	}
+
+	/// Find the last line of the table started by the header entry at the given index, up to the next table header.
+	fn table_end_line(&self, header_index:usize) -> usize {
+		match self.entries[header_index + 1..].iter().find(|entry| entry.is_header) {
+			Some(next_header) => next_header.first_line - 1,
+			None => self.lines.len() - if self.lines.len() > 1 && self.lines.last().is_some_and(|line| line.is_empty()) { 2 } else { 1 }
+		}
+	}
+
+	/// Insert a line at the given index, using the line endings of the document.
+	fn insert_line(&mut self, index:usize, line:&str) {
+		let line_ending:&str = if self.uses_crlf { "\r" } else { "" };
+		if index == self.lines.len() {
+			if let Some(last_line) = self.lines.last_mut() {
+				last_line.push_str(line_ending);
+			}
+			self.lines.push(line.to_string());
+		} else {
+			self.lines.insert(index, format!("{line}{line_ending}"));
+		}
+	}
+
+	/// Find the spacing around the equals sign, preferring the style used in the given table.
+	fn key_separator(&self, table:&str) -> String {
+		self.entries.iter().filter(|entry| entry.is_pair_in(table)).chain(self.entries.iter().filter(|entry| !entry.is_header))
+			.find_map(|entry| {
+				let line:&str = &self.lines[entry.first_line];
+				let equals_index:usize = find_unquoted(line, '=')?;
+				let before:&str = &line[..equals_index];
+				let after:&str = &line[equals_index + 1..];
+				Some(format!("{}={}", &before[before.trim_end().len()..], &after[..after.len() - after.trim_start().len()]))
+			})
+			.unwrap_or_else(|| DEFAULT_KEY_SEPARATOR.to_string())
+	}
+}
+
+
+
+/// Create the entries of the document from the statements found by the reader.
+fn toml_entries(contents:&str, statements:&[TomlStatement]) -> Vec<TomlEntry> {
+	statements.iter().map(|statement| {
+		let text:&str = &contents[statement.span.start..statement.span.end];
+		let first_line:usize = statement.span.line - 1;
+		TomlEntry {
+			value: if statement.is_header { String::new() } else { find_unquoted(text, '=').map(|equals_index| text[equals_index + 1..].trim().to_string()).unwrap_or_default() },
+			table: statement.table.clone(),
+			keys: statement.keys.clone(),
+			is_header: statement.is_header,
+			first_line,
+			last_line: first_line + text.matches('\n').count()
+		}
+	}).collect()
+}
+
+/// Get the part of a path of keys below the given table, or none if the path is not in the table.
+fn relative_keys(keys:&[String], table:&str) -> Option<Vec<String>> {
+	let table_length:usize = (0..=keys.len()).find(|length| keys[..*length].join(".") == table)?;
+	Some(keys[table_length..].to_vec())
+}
+
+/// Find the first occurrence of a character outside of quoted strings.
+fn find_unquoted(text:&str, target:char) -> Option<usize> {
+	let mut quote:Option<char> = None;
+	let mut escaped:bool = false;
+	for (index, char) in text.char_indices() {
+		match quote {
+			Some('"') if escaped => escaped = false,
+			Some('"') if char == '\\' => escaped = true,
+			Some(quote_char) if char == quote_char => quote = None,
+			Some(_) => {},
+			None if char == '"' || char == '\'' => quote = Some(char),
+			None if char == target => return Some(index),
+			None => {}
+		}
+	}
+	None
+}
\ No newline at end of file
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+	use crate::TomlDocument;
+
+
+	fn parse(contents:&str) -> TomlDocument {
+		TomlDocument::parse("Cargo.toml", contents).unwrap()
+	}
+
+	#[test]
+	fn test_insert_preserves_formatting() {
+		let contents:&str = "# Manifest.\n[package]\nname=\"test\"\n\n[dependencies]\n# Comment above.\nalpha={ version=\"1.0\" } # Trailing comment.\n\nzeta=\"2.0\"\n";
+		let mut document:TomlDocument = parse(contents);
+		assert!(document.insert("dependencies", "beta", "\"3.0\""));
+
+		let expected:&str = "# Manifest.\n[package]\nname=\"test\"\n\n[dependencies]\n# Comment above.\nalpha={ version=\"1.0\" } # Trailing comment.\n\nbeta=\"3.0\"\nzeta=\"2.0\"\n";
+		assert_eq!(document.contents(), expected);
+	}
+
+	#[test]
+	fn test_insert_matches_key_spacing() {
+		let mut document:TomlDocument = parse("[package]\nname = \"test\"\n\n[dependencies]\nalpha = \"1.0\"\n");
+		assert!(document.insert("dependencies", "beta", "\"2.0\""));
+		assert_eq!(document.contents(), "[package]\nname = \"test\"\n\n[dependencies]\nalpha = \"1.0\"\nbeta = \"2.0\"\n");
+	}
+
+	#[test]
+	fn test_insert_after_last_key_when_unsorted() {
+		let mut document:TomlDocument = parse("[dependencies]\nzeta=\"1.0\"\nalpha=\"2.0\"\n");
+		assert!(document.insert("dependencies", "beta", "\"3.0\""));
+		assert_eq!(document.contents(), "[dependencies]\nzeta=\"1.0\"\nalpha=\"2.0\"\nbeta=\"3.0\"\n");
+	}
+
+	#[test]
+	fn test_insert_keeps_crlf_line_endings() {
+		let mut document:TomlDocument = parse("[package]\r\nname=\"test\"\r\n\r\n[dependencies]\r\nalpha=\"1.0\"\r\n");
+		assert!(document.insert("dependencies", "beta", "\"2.0\""));
+		assert_eq!(document.contents(), "[package]\r\nname=\"test\"\r\n\r\n[dependencies]\r\nalpha=\"1.0\"\r\nbeta=\"2.0\"\r\n");
+	}
+
+	#[test]
+	fn test_insert_adds_missing_table() {
+		let mut document:TomlDocument = parse("[package]\nname = \"test\"\n");
+		assert!(document.insert("dependencies", "alpha", "\"1.0\""));
+		assert_eq!(document.contents(), "[package]\nname = \"test\"\n\n[dependencies]\nalpha = \"1.0\"\n");
+
+		let mut document:TomlDocument = parse("[package]\nname = \"test\"");
+		assert!(document.insert("dependencies", "alpha", "\"1.0\""));
+		assert_eq!(document.contents(), "[package]\nname = \"test\"\n\n[dependencies]\nalpha = \"1.0\"");
+	}
+
+	#[test]
+	fn test_insert_into_empty_table() {
+		let mut document:TomlDocument = parse("[dependencies]\n\n[features]\ndefault=[]\n");
+		assert!(document.insert("dependencies", "alpha", "\"1.0\""));
+		assert_eq!(document.contents(), "[dependencies]\nalpha=\"1.0\"\n\n[features]\ndefault=[]\n");
+	}
+
+	#[test]
+	fn test_multi_line_values() {
+		let contents:&str = "[dependencies]\nalpha = { version = \"1.0\", features = [\n\t\"first\", # Comment with = sign.\n\t\"second\"\n] }\nzeta = \"2.0\"\n";
+		let mut document:TomlDocument = parse(contents);
+		assert_eq!(document.keys("dependencies"), vec!["alpha".to_string(), "zeta".to_string()]);
+
+		assert!(document.insert("dependencies", "beta", "\"3.0\""));
+		assert_eq!(document.contents(), "[dependencies]\nalpha = { version = \"1.0\", features = [\n\t\"first\", # Comment with = sign.\n\t\"second\"\n] }\nbeta = \"3.0\"\nzeta = \"2.0\"\n");
+	}
+
+	#[test]
+	fn test_get_and_tables() {
+		let document:TomlDocument = parse("[package]\nname = \"test\" # The name.\n\n[\"target\".'cfg(unix)'.dependencies]\n\"quoted\" = \"1.0\"\n");
+		assert_eq!(document.get("package", "name"), Some("\"test\"".to_string()));
+		assert_eq!(document.tables(), vec!["package".to_string(), "target.cfg(unix).dependencies".to_string()]);
+		assert_eq!(document.keys("target.cfg(unix).dependencies"), vec!["quoted".to_string()]);
+	}
+
+	#[test]
+	fn test_remove() {
+		let mut document:TomlDocument = parse("[dependencies]\nalpha = \"1.0\"\nbeta = { version = \"2.0\", features = [\n\t\"first\"\n] }\n\n[dependencies.gamma]\nversion = \"3.0\"\n");
+		assert!(document.remove("dependencies", "beta"));
+		assert!(document.remove("dependencies", "gamma"));
+		assert!(!document.remove("dependencies", "delta"));
+		assert_eq!(document.contents(), "[dependencies]\nalpha = \"1.0\"\n");
+	}
+
+	#[test]
+	fn test_move_key() {
+		let mut document:TomlDocument = parse("[dependencies]\nalpha = \"1.0\"\nbeta = \"2.0\" # Comment.\n\n[dependencies.gamma]\nversion = \"3.0\"\n");
+		assert!(document.move_key("dependencies", "dev-dependencies", "beta"));
+		assert!(document.move_key("dependencies", "dev-dependencies", "gamma"));
+		assert_eq!(document.contents(), "[dependencies]\nalpha = \"1.0\"\n\n[dev-dependencies.gamma]\nversion = \"3.0\"\n\n[dev-dependencies]\nbeta = \"2.0\" # Comment.\n");
+	}
+
+	#[test]
+	fn test_dotted_keys() {
+		let mut document:TomlDocument = parse("[dependencies]\nalpha = \"1.0\"\nbeta.version = \"2.0\"\nbeta.features = [\"first\"]\ngamma = \"3.0\"\n");
+		assert_eq!(document.keys("dependencies"), vec!["alpha".to_string(), "beta".to_string(), "gamma".to_string()]);
+		assert_eq!(document.get("dependencies", "beta"), None);
+		assert!(!document.insert("dependencies", "beta", "\"4.0\""));
+		assert!(document.move_key("dependencies", "dev-dependencies", "beta"));
+		assert!(document.remove("dependencies", "gamma"));
+		assert_eq!(document.contents(), "[dependencies]\nalpha = \"1.0\"\n\n[dev-dependencies]\nbeta.version = \"2.0\"\nbeta.features = [\"first\"]\n");
+
+		// Keys defined by dotted keys outside of the table can be removed, but not moved or added to.
+		let mut document:TomlDocument = parse("dependencies.alpha = \"1.0\"\ndependencies.beta = \"2.0\"\n");
+		assert_eq!(document.keys("dependencies"), vec!["alpha".to_string(), "beta".to_string()]);
+		assert!(!document.move_key("dependencies", "dev-dependencies", "alpha"));
+		assert!(!document.insert("dependencies", "gamma", "\"3.0\""));
+		assert!(document.remove("dependencies", "alpha"));
+		assert_eq!(document.contents(), "dependencies.beta = \"2.0\"\n");
+	}
+
+	#[test]
+	fn test_inline_tables() {
+		let mut document:TomlDocument = parse("[dependencies]\nalpha = { version = \"1.0\", path = \"../alpha\" }\nbeta = {\n\tversion = \"2.0\",\n\tgamma = \"x\"\n}\ndelta = \"3.0\"\n");
+		assert_eq!(document.keys("dependencies"), vec!["alpha".to_string(), "beta".to_string(), "delta".to_string()]);
+		assert_eq!(document.get("dependencies", "alpha"), Some("{ version = \"1.0\", path = \"../alpha\" }".to_string()));
+		assert!(!document.remove("dependencies", "gamma"));
+		assert!(document.remove("dependencies", "beta"));
+		assert_eq!(document.contents(), "[dependencies]\nalpha = { version = \"1.0\", path = \"../alpha\" }\ndelta = \"3.0\"\n");
+	}
+
+	#[test]
+	fn test_invalid_documents() {
+		assert!(TomlDocument::parse("Cargo.toml", "[dependencies]\nalpha = \"1.0\"\nalpha = \"2.0\"\n").is_err());
+		assert!(TomlDocument::parse("Cargo.toml", "[dependencies]\nalpha = \n").is_err());
+	}
+}
\ No newline at end of file
@@ -0,0 +1,571 @@
+use crate::{ Error, LineIndex, Span };
+
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TomlValue {
+	String(String),
+	Integer(i64),
+	Float(f64),
+	Boolean(bool),
+	Datetime(String),
+	Array(Vec<TomlValue>),
+	Table(Vec<(String, TomlValue)>)
+}
+impl TomlValue {
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Get the value of a key if this is a table.
+	pub fn get(&self, key:&str) -> Option<&TomlValue> {
+		self.as_table()?.iter().find(|(table_key, _)| table_key == key).map(|(_, value)| value)
+	}
+
+	/// Get the value at a path of keys through nested tables.
+	pub fn get_path(&self, path:&[&str]) -> Option<&TomlValue> {
+		path.iter().try_fold(self, |value, key| value.get(key))
+	}
+
+	/// Get the keys of this table, in order of definition. Returns an empty list if this is not a table.
+	pub fn keys(&self) -> Vec<&str> {
+		self.as_table().map(|table| table.iter().map(|(key, _)| key.as_str()).collect()).unwrap_or_default()
+	}
+
+	/// Get the value as a string, if it is one.
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			TomlValue::String(value) => Some(value),
+			_ => None
+		}
+	}
+
+	/// Get the value as a boolean, if it is one.
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			TomlValue::Boolean(value) => Some(*value),
+			_ => None
+		}
+	}
+
+	/// Get the value as an integer, if it is one.
+	pub fn as_integer(&self) -> Option<i64> {
+		match self {
+			TomlValue::Integer(value) => Some(*value),
+			_ => None
+		}
+	}
+
+	/// Get the items of the value, if it is an array.
+	pub fn as_array(&self) -> Option<&[TomlValue]> {
+		match self {
+			TomlValue::Array(items) => Some(items),
+			_ => None
+		}
+	}
+
+	/// Get the key-value pairs of the value, if it is a table.
+	pub fn as_table(&self) -> Option<&[(String, TomlValue)]> {
+		match self {
+			TomlValue::Table(table) => Some(table),
+			_ => None
+		}
+	}
+}
+
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlStatement {
+	pub table:Vec<String>,
+	pub keys:Vec<String>,
+	pub is_header:bool,
+	pub span:Span
+}
+impl TomlStatement {
+
+	/* PROPERTY GETTER METHODS */
+
+	/// Get the full path of keys the statement defines. For a table header this is the path of the table.
+	pub fn full_keys(&self) -> Vec<String> {
+		self.table.iter().chain(&self.keys).cloned().collect()
+	}
+}
+
+
+
+/// Read the contents of a TOML file into a table value. Dotted keys, quoted keys and dotted table headers are expanded into nested tables, so `[dependencies.foo]` and `foo.version` in `[dependencies]` both define the `foo` key of the `dependencies` table.
+pub fn read_toml(path:&str, contents:&str) -> Result<TomlValue, Error> {
+	read_toml_statements(path, contents).map(|(value, _)| value)
+}
+
+/// Find the location of the statement defining the value at the path of keys. Without a statement for the full value, a statement defining part of it, like a dotted key or sub-table header, is used, and otherwise the closest parent. Values inside inline tables and arrays are located by the statement of the table or array.
+pub fn statement_span(statements:&[TomlStatement], keys:&[&str]) -> Option<Span> {
+	(1..=keys.len()).rev().find_map(|length| {
+		let starts_with_keys = |statement:&&TomlStatement| statement.full_keys().iter().take(length).eq(keys[..length].iter());
+		let defining_statements:Vec<&TomlStatement> = statements.iter().filter(starts_with_keys).collect();
+		defining_statements.iter().find(|statement| statement.full_keys().len() == length).or(defining_statements.first()).map(|statement| statement.span.clone())
+	})
+}
+
+/// Read the contents of a TOML file into a table value, together with the table headers and key-value pairs in order of appearance. Each statement has the table it is defined in and the location of its full text.
+pub fn read_toml_statements(path:&str, contents:&str) -> Result<(TomlValue, Vec<TomlStatement>), Error> {
+	TomlReader::new(path, contents).read()
+}
+
+
+
+struct TomlReader<'a> {
+	path:&'a str,
+	text:&'a str,
+	bytes:&'a [u8],
+	cursor:usize,
+	line_index:LineIndex,
+	root:Vec<(String, TomlValue)>,
+	table_path:Vec<String>,
+	defined_tables:Vec<Vec<String>>,
+	statements:Vec<TomlStatement>
+}
+impl<'a> TomlReader<'a> {
+
+	/* CONSTRUCTOR METHODS */
+
+	/// Create a new reader for the given contents.
+	fn new(path:&'a str, text:&'a str) -> TomlReader<'a> {
+		TomlReader {
+			path,
+			text,
+			bytes: text.as_bytes(),
+			cursor: 0,
+			line_index: LineIndex::new(text),
+			root: Vec::new(),
+			table_path: Vec::new(),
+			defined_tables: Vec::new(),
+			statements: Vec::new()
+		}
+	}
+
+
+
+	/* READING METHODS */
+
+	/// Read the full document.
+	fn read(mut self) -> Result<(TomlValue, Vec<TomlStatement>), Error> {
+		loop {
+			self.skip_blank();
+			if self.cursor >= self.bytes.len() {
+				break;
+			}
+			if self.peek(0) == b'[' {
+				self.read_table_header()?;
+			} else {
+				self.read_key_value()?;
+			}
+			self.expect_line_end()?;
+		}
+		Ok((TomlValue::Table(self.root), self.statements))
+	}
+
+	/// Read a table or array-of-tables header and make it the current table.
+	fn read_table_header(&mut self) -> Result<(), Error> {
+		let start:usize = self.cursor;
+		let is_array:bool = self.text[self.cursor..].starts_with("[[");
+		self.cursor += if is_array { 2 } else { 1 };
+		let keys:Vec<String> = self.read_key()?;
+		self.expect(if is_array { "]]" } else { "]" })?;
+
+		let path:&str = self.path;
+		let span:Span = self.span(start);
+		let error = |message:String| Error::manifest(path, Some(span.clone()), &message);
+		if is_array {
+			let (name, parent_keys) = keys.split_last().unwrap();
+			let parent:&mut Vec<(String, TomlValue)> = table_at(&mut self.root, parent_keys).map_err(error)?;
+			match parent.iter_mut().find(|(key, _)| key == name) {
+				Some((_, TomlValue::Array(items))) if items.iter().all(|item| matches!(item, TomlValue::Table(_))) => items.push(TomlValue::Table(Vec::new())),
+				Some(_) => return Err(error(format!("key `{}` is already defined and is not an array of tables", keys.join(".")))),
+				None => parent.push((name.clone(), TomlValue::Array(vec![TomlValue::Table(Vec::new())])))
+			}
+			self.defined_tables.retain(|table| !table.starts_with(&keys));
+		} else {
+			if self.defined_tables.contains(&keys) {
+				return Err(error(format!("table `{}` is defined twice", keys.join("."))));
+			}
+			table_at(&mut self.root, &keys).map_err(error)?;
+			self.defined_tables.push(keys.clone());
+		}
+		self.statements.push(TomlStatement { table: keys.clone(), keys: Vec::new(), is_header: true, span });
+		self.table_path = keys;
+		Ok(())
+	}
+
+	/// Read a key-value pair into the current table.
+	fn read_key_value(&mut self) -> Result<(), Error> {
+		let start:usize = self.cursor;
+		let (keys, value) = self.read_key_value_pair()?;
+		let full_keys:Vec<String> = self.table_path.iter().cloned().chain(keys.iter().cloned()).collect();
+		let path:&str = self.path;
+		let span:Span = self.span(start);
+		insert_value(&mut self.root, &full_keys, value).map_err(|message| Error::manifest(path, Some(span.clone()), &message))?;
+
+		// Tables created by dotted keys cannot be defined again by a header.
+		for table_length in self.table_path.len() + 1..full_keys.len() {
+			if !self.defined_tables.iter().any(|table| table[..] == full_keys[..table_length]) {
+				self.defined_tables.push(full_keys[..table_length].to_vec());
+			}
+		}
+		self.statements.push(TomlStatement { table: self.table_path.clone(), keys, is_header: false, span });
+		Ok(())
+	}
+
+	/// Read a `key = value` pair, returning the dotted key parts and the value.
+	fn read_key_value_pair(&mut self) -> Result<(Vec<String>, TomlValue), Error> {
+		let keys:Vec<String> = self.read_key()?;
+		self.expect("=")?;
+		self.skip_spaces();
+		let value:TomlValue = self.read_value()?;
+		Ok((keys, value))
+	}
+
+	/// Read a bare, quoted or dotted key. Returns the parts of the key.
+	fn read_key(&mut self) -> Result<Vec<String>, Error> {
+		let mut keys:Vec<String> = Vec::new();
+		loop {
+			self.skip_spaces();
+			let start:usize = self.cursor;
+			let key:String = match self.peek(0) {
+				b'"' => self.read_basic_string()?,
+				b'\'' => self.read_literal_string()?,
+				byte if is_bare_key_byte(byte) => {
+					while is_bare_key_byte(self.peek(0)) {
+						self.cursor += 1;
+					}
+					self.text[start..self.cursor].to_string()
+				},
+				_ => return Err(self.error(start, "expected a key"))
+			};
+			keys.push(key);
+			self.skip_spaces();
+			if self.peek(0) != b'.' {
+				return Ok(keys);
+			}
+			self.cursor += 1;
+		}
+	}
+
+	/// Read any value.
+	fn read_value(&mut self) -> Result<TomlValue, Error> {
+		let remaining:&str = &self.text[self.cursor..];
+		match self.peek(0) {
+			b'"' if remaining.starts_with("\"\"\"") => self.read_multi_line_string("\"\"\"", true).map(TomlValue::String),
+			b'"' => self.read_basic_string().map(TomlValue::String),
+			b'\'' if remaining.starts_with("'''") => self.read_multi_line_string("'''", false).map(TomlValue::String),
+			b'\'' => self.read_literal_string().map(TomlValue::String),
+			b'[' => self.read_array(),
+			b'{' => self.read_inline_table(),
+			_ if remaining.starts_with("true") => {
+				self.cursor += 4;
+				Ok(TomlValue::Boolean(true))
+			},
+			_ if remaining.starts_with("false") => {
+				self.cursor += 5;
+				Ok(TomlValue::Boolean(false))
+			},
+			_ => self.read_number_or_datetime()
+		}
+	}
+
+	/// Read a basic string on a single line, handling escapes.
+	fn read_basic_string(&mut self) -> Result<String, Error> {
+		let start:usize = self.cursor;
+		let mut value:String = String::new();
+		self.cursor += 1;
+		loop {
+			match self.text[self.cursor..].chars().next() {
+				Some('"') => {
+					self.cursor += 1;
+					return Ok(value);
+				},
+				Some('\\') => value.push(self.read_escape()?),
+				Some('\n') | None => return Err(self.error(start, "unterminated string")),
+				Some(char) => {
+					value.push(char);
+					self.cursor += char.len_utf8();
+				}
+			}
+		}
+	}
+
+	/// Read a literal string on a single line.
+	fn read_literal_string(&mut self) -> Result<String, Error> {
+		let start:usize = self.cursor;
+		self.cursor += 1;
+		match self.text[self.cursor..].find(['\'', '\n']) {
+			Some(length) if self.bytes[self.cursor + length] == b'\'' => {
+				let value:String = self.text[self.cursor..self.cursor + length].to_string();
+				self.cursor += length + 1;
+				Ok(value)
+			},
+			_ => Err(self.error(start, "unterminated string"))
+		}
+	}
+
+	/// Read a multi-line basic or literal string. A newline directly after the opening delimiter is trimmed. In basic strings, escapes are handled and a backslash at the end of a line trims all following whitespace.
+	fn read_multi_line_string(&mut self, delimiter:&str, is_basic:bool) -> Result<String, Error> {
+		let start:usize = self.cursor;
+		let quote:char = delimiter.chars().next().unwrap();
+		let mut value:String = String::new();
+		self.cursor += delimiter.len();
+		if self.text[self.cursor..].starts_with("\r\n") {
+			self.cursor += 2;
+		} else if self.peek(0) == b'\n' {
+			self.cursor += 1;
+		}
+		loop {
+			let remaining:&str = &self.text[self.cursor..];
+			if remaining.starts_with(delimiter) {
+
+				// Up to two quotes directly before the closing delimiter are part of the value.
+				let quote_count:usize = remaining.chars().take(5).take_while(|char| *char == quote).count();
+				value.push_str(&quote.to_string().repeat(quote_count - 3));
+				self.cursor += quote_count;
+				return Ok(value);
+			}
+			match remaining.chars().next() {
+				Some('\\') if is_basic => {
+					let after_backslash:&str = remaining[1..].trim_start_matches([' ', '\t']);
+					if after_backslash.starts_with('\n') || after_backslash.starts_with("\r\n") {
+						self.cursor = self.text.len() - after_backslash.trim_start_matches([' ', '\t', '\r', '\n']).len();
+					} else {
+						value.push(self.read_escape()?);
+					}
+				},
+				Some(char) => {
+					value.push(char);
+					self.cursor += char.len_utf8();
+				},
+				None => return Err(self.error(start, "unterminated string"))
+			}
+		}
+	}
+
+	/// Read an escape sequence starting with the backslash at the cursor.
+	fn read_escape(&mut self) -> Result<char, Error> {
+		let start:usize = self.cursor;
+		self.cursor += 2;
+		let escaped:char = match self.bytes.get(start + 1) {
+			Some(b'b') => '\u{8}',
+			Some(b't') => '\t',
+			Some(b'n') => '\n',
+			Some(b'f') => '\u{c}',
+			Some(b'r') => '\r',
+			Some(b'e') => '\u{1b}',
+			Some(b'"') => '"',
+			Some(b'\\') => '\\',
+			Some(b'u') => return self.read_unicode_escape(start, 4),
+			Some(b'U') => return self.read_unicode_escape(start, 8),
+			_ => return Err(self.error(start, "invalid escape sequence"))
+		};
+		Ok(escaped)
+	}
+
+	/// Read the hexadecimal digits of a unicode escape sequence.
+	fn read_unicode_escape(&mut self, start:usize, digit_count:usize) -> Result<char, Error> {
+		let digits:Option<&str> = self.text.get(self.cursor..self.cursor + digit_count);
+		match digits.and_then(|digits| u32::from_str_radix(digits, 16).ok()).and_then(char::from_u32) {
+			Some(char) => {
+				self.cursor += digit_count;
+				Ok(char)
+			},
+			None => Err(self.error(start, "invalid unicode escape sequence"))
+		}
+	}
+
+	/// Read an array, which may span multiple lines and contain comments and a trailing comma.
+	fn read_array(&mut self) -> Result<TomlValue, Error> {
+		let mut items:Vec<TomlValue> = Vec::new();
+		self.cursor += 1;
+		loop {
+			self.skip_blank();
+			if self.peek(0) == b']' {
+				self.cursor += 1;
+				return Ok(TomlValue::Array(items));
+			}
+			items.push(self.read_value()?);
+			self.skip_blank();
+			match self.peek(0) {
+				b',' => self.cursor += 1,
+				b']' => {},
+				_ => return Err(self.error(self.cursor, "expected `,` or `]` in array"))
+			}
+		}
+	}
+
+	/// Read an inline table. Newlines and a trailing comma are accepted inside the braces.
+	fn read_inline_table(&mut self) -> Result<TomlValue, Error> {
+		let mut table:Vec<(String, TomlValue)> = Vec::new();
+		self.cursor += 1;
+		loop {
+			self.skip_blank();
+			if self.peek(0) == b'}' {
+				self.cursor += 1;
+				return Ok(TomlValue::Table(table));
+			}
+			let start:usize = self.cursor;
+			let (keys, value) = self.read_key_value_pair()?;
+			insert_value(&mut table, &keys, value).map_err(|message| self.error(start, &message))?;
+			self.skip_blank();
+			match self.peek(0) {
+				b',' => self.cursor += 1,
+				b'}' => {},
+				_ => return Err(self.error(self.cursor, "expected `,` or `}` in inline table"))
+			}
+		}
+	}
+
+	/// Read an integer, float or date-time value.
+	fn read_number_or_datetime(&mut self) -> Result<TomlValue, Error> {
+		let start:usize = self.cursor;
+		let is_value_byte = |byte:u8| byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'-' | b'_' | b'.' | b':');
+		while is_value_byte(self.peek(0)) {
+			self.cursor += 1;
+		}
+
+		// A date and time may be separated by a space.
+		let is_date:bool = self.cursor - start == 10 && self.bytes[start + 4] == b'-';
+		if is_date && self.peek(0) == b' ' && self.peek(1).is_ascii_digit() && self.peek(2).is_ascii_digit() && self.peek(3) == b':' {
+			self.cursor += 1;
+			while is_value_byte(self.peek(0)) {
+				self.cursor += 1;
+			}
+		}
+
+		let text:&str = &self.text[start..self.cursor];
+		let digits:String = text.replace('_', "");
+		let unsigned:&str = digits.trim_start_matches(['+', '-']);
+		let value:Option<TomlValue> = if text.is_empty() {
+			None
+		} else if text.len() >= 8 && (text.as_bytes().get(4) == Some(&b'-') || text.as_bytes().get(2) == Some(&b':')) {
+			Some(TomlValue::Datetime(text.to_string()))
+		} else if matches!(unsigned, "inf" | "nan") {
+			let sign:f64 = if digits.starts_with('-') { -1.0 } else { 1.0 };
+			Some(TomlValue::Float(if unsigned == "inf" { sign * f64::INFINITY } else { f64::NAN }))
+		} else if let Some((prefix, radix)) = [("0x", 16), ("0o", 8), ("0b", 2)].into_iter().find(|(prefix, _)| digits.starts_with(prefix)) {
+			i64::from_str_radix(&digits[prefix.len()..], radix).ok().map(TomlValue::Integer)
+		} else if digits.contains(['.', 'e', 'E']) {
+			digits.parse::<f64>().ok().map(TomlValue::Float)
+		} else {
+			digits.parse::<i64>().ok().map(TomlValue::Integer)
+		};
+		value.ok_or_else(|| self.error(start, "invalid value"))
+	}
+
+
+
+	/* HELPER METHODS */
+
+	/// Get the byte at the given offset from the cursor, or 0 if out of bounds.
+	fn peek(&self, offset:usize) -> u8 {
+		self.bytes.get(self.cursor + offset).copied().unwrap_or(0)
+	}
+
+	/// Move the cursor past spaces and tabs.
+	fn skip_spaces(&mut self) {
+		while matches!(self.peek(0), b' ' | b'\t') {
+			self.cursor += 1;
+		}
+	}
+
+	/// Move the cursor past whitespace, newlines and comments.
+	fn skip_blank(&mut self) {
+		loop {
+			match self.peek(0) {
+				b' ' | b'\t' | b'\r' | b'\n' => self.cursor += 1,
+				b'#' => {
+					while self.cursor < self.bytes.len() && self.peek(0) != b'\n' {
+						self.cursor += 1;
+					}
+				},
+				_ => return
+			}
+		}
+	}
+
+	/// Move the cursor past the expected text, after optional spaces.
+	fn expect(&mut self, expected:&str) -> Result<(), Error> {
+		self.skip_spaces();
+		if self.text[self.cursor..].starts_with(expected) {
+			self.cursor += expected.len();
+			Ok(())
+		} else {
+			Err(self.error(self.cursor, &format!("expected `{expected}`")))
+		}
+	}
+
+	/// Move the cursor past the end of the line, allowing only spaces and a comment before it.
+	fn expect_line_end(&mut self) -> Result<(), Error> {
+		self.skip_spaces();
+		if self.peek(0) == b'#' {
+			while self.cursor < self.bytes.len() && self.peek(0) != b'\n' {
+				self.cursor += 1;
+			}
+		}
+		if self.text[self.cursor..].starts_with("\r\n") {
+			self.cursor += 2;
+		} else if self.peek(0) == b'\n' {
+			self.cursor += 1;
+		} else if self.cursor < self.bytes.len() {
+			return Err(self.error(self.cursor, "expected the end of the line"));
+		}
+		Ok(())
+	}
+
+	/// Create a span from the given offset up to the cursor.
+	fn span(&self, start:usize) -> Span {
+		self.line_index.span(self.text, start, self.cursor.max(start))
+	}
+
+	/// Create a manifest error starting at the given offset.
+	fn error(&self, start:usize, message:&str) -> Error {
+		Error::manifest(self.path, Some(self.span(start)), message)
+	}
+}
+
+
+
+/// Whether or not the byte can be part of a bare key.
+fn is_bare_key_byte(byte:u8) -> bool {
+	byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-'
+}
+
+/// Find the table at the path of keys, creating missing tables on the way. The last table of an array of tables is used when the path passes through one.
+fn table_at<'a>(mut table:&'a mut Vec<(String, TomlValue)>, keys:&[String]) -> Result<&'a mut Vec<(String, TomlValue)>, String> {
+	for (key_index, key) in keys.iter().enumerate() {
+		let index:usize = match table.iter().position(|(table_key, _)| table_key == key) {
+			Some(index) => index,
+			None => {
+				table.push((key.clone(), TomlValue::Table(Vec::new())));
+				table.len() - 1
+			}
+		};
+		let not_a_table = || format!("key `{}` is already defined and is not a table", keys[..=key_index].join("."));
+		table = match &mut table[index].1 {
+			TomlValue::Table(sub_table) => sub_table,
+			TomlValue::Array(items) => match items.last_mut() {
+				Some(TomlValue::Table(sub_table)) => sub_table,
+				_ => return Err(not_a_table())
+			},
+			_ => return Err(not_a_table())
+		};
+	}
+	Ok(table)
+}
+
+/// Insert a value at the path of keys, creating missing tables on the way.
+fn insert_value(table:&mut Vec<(String, TomlValue)>, keys:&[String], value:TomlValue) -> Result<(), String> {
+	let (name, parent_keys) = keys.split_last().unwrap();
+	let parent:&mut Vec<(String, TomlValue)> = table_at(table, parent_keys)?;
+	if parent.iter().any(|(key, _)| key == name) {
+		return Err(format!("key `{}` is defined twice", keys.join(".")));
+	}
+	parent.push((name.clone(), value));
+	Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ read_toml, read_toml_statements, statement_span, TomlStatement, TomlValue };
+
+
+	fn read(contents:&str) -> TomlValue {
+		read_toml("Cargo.toml", contents).unwrap()
+	}
+
+	#[test]
+	fn test_multi_line_array() {
+		let manifest:TomlValue = read("[package]\nauthors = [\n\t\"first\", # Comment.\n\t\"second\",\n]\nname = \"test\"\n");
+		assert_eq!(manifest.get_path(&["package", "authors"]), Some(&TomlValue::Array(vec![TomlValue::String("first".to_string()), TomlValue::String("second".to_string())])));
+		assert_eq!(manifest.get_path(&["package", "name"]).and_then(|name| name.as_str()), Some("test"));
+	}
+
+	#[test]
+	fn test_multi_line_inline_table() {
+		let manifest:TomlValue = read("[dependencies]\nfoo = { version = \"1.0\",\n\tfeatures = [\"a\"] }\nbar = \"2.0\"\n");
+		assert_eq!(manifest.get_path(&["dependencies", "foo", "version"]).and_then(|version| version.as_str()), Some("1.0"));
+		assert_eq!(manifest.get("dependencies").unwrap().keys(), vec!["foo", "bar"]);
+	}
+
+	#[test]
+	fn test_dotted_table_header() {
+		let manifest:TomlValue = read("[dependencies]\nbar = \"2.0\"\n\n[dependencies.foo]\nversion = \"1.0\"\ndefault-features = false\n");
+		assert_eq!(manifest.get("dependencies").unwrap().keys(), vec!["bar", "foo"]);
+		assert_eq!(manifest.get_path(&["dependencies", "foo", "default-features"]).and_then(|value| value.as_bool()), Some(false));
+	}
+
+	#[test]
+	fn test_dotted_keys() {
+		let manifest:TomlValue = read("[dependencies]\nfoo.version = \"1.0\"\nfoo . path = \"../foo\"\n");
+		assert_eq!(manifest.get("dependencies").unwrap().keys(), vec!["foo"]);
+		assert_eq!(manifest.get_path(&["dependencies", "foo", "path"]).and_then(|path| path.as_str()), Some("../foo"));
+	}
+
+	#[test]
+	fn test_quoted_keys() {
+		let manifest:TomlValue = read("[target.'cfg(unix)'.dependencies]\n\"foo.bar\" = \"1.0\"\n'baz' = \"2.0\"\n");
+		assert_eq!(manifest.get_path(&["target", "cfg(unix)", "dependencies"]).unwrap().keys(), vec!["foo.bar", "baz"]);
+	}
+
+	#[test]
+	fn test_equals_and_hash_inside_strings() {
+		let manifest:TomlValue = read("[package]\ndescription = \"a = b # not a comment\" # A comment.\nrepository = 'x=y'\n");
+		assert_eq!(manifest.get_path(&["package", "description"]).and_then(|value| value.as_str()), Some("a = b # not a comment"));
+		assert_eq!(manifest.get_path(&["package", "repository"]).and_then(|value| value.as_str()), Some("x=y"));
+	}
+
+	#[test]
+	fn test_strings() {
+		let manifest:TomlValue = read("escaped = \"tab\\t quote\\\" \\u00e9\"\nmulti = \"\"\"\nfirst \\\n\t second\"\"\"\nliteral = '''\nC:\\path\n'''\n");
+		assert_eq!(manifest.get("escaped").and_then(|value| value.as_str()), Some("tab\t quote\" \u{e9}"));
+		assert_eq!(manifest.get("multi").and_then(|value| value.as_str()), Some("first second"));
+		assert_eq!(manifest.get("literal").and_then(|value| value.as_str()), Some("C:\\path\n"));
+	}
+
+	#[test]
+	fn test_numbers_booleans_and_datetimes() {
+		let manifest:TomlValue = read("integer = 1_000\nhex = 0xff\nfloat = -1.5e2\nboolean = true\ndate = 1979-05-27 07:32:00Z\n");
+		assert_eq!(manifest.get("integer"), Some(&TomlValue::Integer(1000)));
+		assert_eq!(manifest.get("hex"), Some(&TomlValue::Integer(255)));
+		assert_eq!(manifest.get("float"), Some(&TomlValue::Float(-150.0)));
+		assert_eq!(manifest.get("boolean"), Some(&TomlValue::Boolean(true)));
+		assert_eq!(manifest.get("date"), Some(&TomlValue::Datetime("1979-05-27 07:32:00Z".to_string())));
+	}
+
+	#[test]
+	fn test_array_of_tables() {
+		let manifest:TomlValue = read("[[bin]]\nname = \"first\"\n\n[[bin]]\nname = \"second\"\n");
+		let names:Vec<&str> = manifest.get("bin").and_then(|bins| bins.as_array()).unwrap().iter().filter_map(|bin| bin.get("name")?.as_str()).collect();
+		assert_eq!(names, vec!["first", "second"]);
+	}
+
+	#[test]
+	fn test_crlf_line_endings() {
+		let manifest:TomlValue = read("[package]\r\nname = \"test\"\r\n\r\n[dependencies]\r\nfoo = \"1.0\"\r\n");
+		assert_eq!(manifest.get("dependencies").unwrap().keys(), vec!["foo"]);
+	}
+
+	#[test]
+	fn test_invalid_documents() {
+		assert!(read_toml("Cargo.toml", "[dependencies]\nfoo = \"1.0\"\nfoo = \"2.0\"\n").is_err());
+		assert!(read_toml("Cargo.toml", "[dependencies]\nfoo = \"1.0\"\n[dependencies.foo]\nversion = \"1.0\"\n").is_err());
+		assert!(read_toml("Cargo.toml", "[package]\n[package]\n").is_err());
+		assert!(read_toml("Cargo.toml", "dependencies.foo = \"1.0\"\n[dependencies]\nbar = \"2.0\"\n").is_err());
+		assert!(read_toml("Cargo.toml", "[dependencies]\nfoo.version = \"1.0\"\n[dependencies.foo]\npath = \"../foo\"\n").is_err());
+		assert!(read_toml("Cargo.toml", "name = \"unterminated\n").is_err());
+		assert!(read_toml("Cargo.toml", "name = \"test\" trailing\n").is_err());
+
+		let error = read_toml("Cargo.toml", "[package]\nname = \n").unwrap_err();
+		assert_eq!(error.span().map(|span| span.line), Some(2));
+	}
+
+	#[test]
+	fn test_statements() {
+		let contents:&str = "[dependencies]\nfoo.version = \"1.0\" # Comment.\nbar = {\n\tpath = \"../bar\"\n}\n";
+		let (_, statements) = read_toml_statements("Cargo.toml", contents).unwrap();
+		let summary:Vec<(Vec<String>, bool, usize)> = statements.iter().map(|statement:&TomlStatement| (statement.full_keys(), statement.is_header, statement.span.line)).collect();
+		assert_eq!(summary, vec![
+			(vec!["dependencies".to_string()], true, 1),
+			(vec!["dependencies".to_string(), "foo".to_string(), "version".to_string()], false, 2),
+			(vec!["dependencies".to_string(), "bar".to_string()], false, 3)
+		]);
+		assert_eq!(&contents[statements[1].span.start..statements[1].span.end], "foo.version = \"1.0\"");
+		assert_eq!(&contents[statements[2].span.start..statements[2].span.end], "bar = {\n\tpath = \"../bar\"\n}");
+
+		// Values without their own statement are located by the statement defining part of them or their parent.
+		let span_line = |keys:&[&str]| statement_span(&statements, keys).map(|span| span.line);
+		assert_eq!(span_line(&["dependencies", "foo"]), Some(2));
+		assert_eq!(span_line(&["dependencies", "bar", "path"]), Some(3));
+		assert_eq!(span_line(&["dependencies", "missing"]), Some(1));
+		assert_eq!(span_line(&["package"]), None);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,34 @@
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+	pub start:usize,
+	pub end:usize,
+	pub line:usize,
+	pub column:usize
+}
+
+pub(crate) struct LineIndex {
+	line_starts:Vec<usize>
+}
+impl LineIndex {
+
+	/// Create a new line index for the given contents.
+	pub fn new(contents:&str) -> LineIndex {
+		LineIndex {
+			line_starts: [0].into_iter().chain(contents.match_indices('\n').map(|(index, _)| index + 1)).collect()
+		}
+	}
+
+	/// Create a span for a byte range in the indexed contents. Lines and columns start at 1.
+	pub fn span(&self, contents:&str, start:usize, end:usize) -> Span {
+		let line_index:usize = match self.line_starts.binary_search(&start) {
+			Ok(index) => index,
+			Err(index) => index - 1
+		};
+		let line_start:usize = self.line_starts[line_index];
+		let column:usize = match contents.get(line_start..start) {
+			Some(line_prefix) => line_prefix.chars().count() + 1,
+			None => start - line_start + 1
+		};
+		Span { start, end, line: line_index + 1, column }
+	}
+}
\ No newline at end of file
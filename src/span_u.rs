@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ LineIndex, Span };
+
+
+	fn span(contents:&str, start:usize, end:usize) -> Span {
+		LineIndex::new(contents).span(contents, start, end)
+	}
+
+	#[test]
+	fn test_lines_and_columns() {
+		let contents:&str = "first\nsecond\n\nfourth";
+		assert_eq!(span(contents, 0, 5), Span { start: 0, end: 5, line: 1, column: 1 });
+		assert_eq!(span(contents, 8, 12), Span { start: 8, end: 12, line: 2, column: 3 });
+		assert_eq!(span(contents, 13, 13), Span { start: 13, end: 13, line: 3, column: 1 });
+		assert_eq!(span(contents, contents.len(), contents.len()).column, 7);
+	}
+
+	#[test]
+	fn test_multi_byte_characters() {
+
+		// Columns count characters, not bytes.
+		let contents:&str = "é = \"ü\"\nnäme = 1\n";
+		let value_start:usize = contents.find('1').unwrap();
+		assert_eq!(span(contents, value_start, value_start + 1), Span { start: value_start, end: value_start + 1, line: 2, column: 8 });
+		let quote_start:usize = contents.find('"').unwrap();
+		assert_eq!(span(contents, quote_start, quote_start + 4).column, 5);
+	}
+
+	#[test]
+	fn test_crlf_line_endings() {
+
+		// The carriage return belongs to the end of the line and does not shift the next line.
+		let contents:&str = "[package]\r\nname = \"test\"\r\n";
+		let name_start:usize = contents.find("name").unwrap();
+		assert_eq!(span(contents, name_start, name_start + 4), Span { start: name_start, end: name_start + 4, line: 2, column: 1 });
+		let value_start:usize = contents.find("\"test\"").unwrap();
+		assert_eq!(span(contents, value_start, value_start + 6).column, 8);
+		assert_eq!(span(contents, 9, 10), Span { start: 9, end: 10, line: 1, column: 10 });
+	}
+}
\ No newline at end of file
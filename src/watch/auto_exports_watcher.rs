@@ -0,0 +1,86 @@
+use crate::{ content_hash, Error, FileChange, FileWatcher, ItemImportExportUpdater };
+use std::{ path::Path, time::Duration };
+use file_ref::FileRef;
+
+
+
+const DEFAULT_DEBOUNCE:Duration = Duration::from_millis(250);
+
+
+
+pub struct WatchReport {
+	pub changed_files:Vec<String>,
+	pub rewritten_files:Vec<String>,
+	pub rescanned:bool
+}
+
+pub struct AutoExportsWatcher {
+	updater:ItemImportExportUpdater,
+	watcher:FileWatcher,
+	debounce:Duration,
+	written_files:Vec<(String, u64)>
+}
+impl AutoExportsWatcher {
+
+	/// Create a new watcher for the crate of the given updater, so changes are handled with the same configuration as a regular generation. Generates all auto-exports once and watches the directory of the root file for changes.
+	pub fn new(mut updater:ItemImportExportUpdater) -> Result<AutoExportsWatcher, Error> {
+		let root_file:FileRef = FileRef::new(updater.file());
+		let source_dir:FileRef = root_file.parent_dir().map_err(|error| Error::io(root_file.path(), error))?;
+		updater.generate()?;
+		Ok(AutoExportsWatcher {
+			updater,
+			watcher: FileWatcher::new(source_dir.path())?,
+			debounce: DEFAULT_DEBOUNCE,
+			written_files: Vec::new()
+		})
+	}
+
+	/// Return self with a specific debounce time. Changes are only processed after no new changes occurred for this duration.
+	pub fn with_debounce(mut self, debounce:Duration) -> Self {
+		self.debounce = debounce;
+		self
+	}
+
+	/// Get the updater of the watched crate.
+	pub fn updater(&self) -> &ItemImportExportUpdater {
+		&self.updater
+	}
+
+	/// Wait for the next set of changes and re-generate the affected auto-exports.
+	pub fn next_report(&mut self) -> Result<WatchReport, Error> {
+		let changes:Vec<FileChange> = self.wait_for_external_changes()?;
+		self.regenerate(changes)
+	}
+
+	/// Keep watching for changes, calling the handler with the result of every re-generation. Failed re-generations are passed to the handler, only returns when the file watcher itself fails.
+	pub fn run<T:FnMut(Result<WatchReport, Error>)>(&mut self, mut handler:T) -> Result<(), Error> {
+		loop {
+			let changes:Vec<FileChange> = self.wait_for_external_changes()?;
+			handler(self.regenerate(changes));
+		}
+	}
+
+	/// Wait for the next set of changes, ignoring changes caused by the watcher's own writes.
+	fn wait_for_external_changes(&self) -> Result<Vec<FileChange>, Error> {
+		loop {
+			let changes:Vec<FileChange> = self.watcher.wait_for_changes(self.debounce)?.into_iter().filter(|change| !matches!(change, FileChange::Path(path) if self.is_own_write(path))).collect();
+			if !changes.is_empty() {
+				return Ok(changes);
+			}
+		}
+	}
+
+	/// Re-generate the auto-exports affected by the changes, or all auto-exports when the watcher lost track of changes. Remembers the contents of all rewritten files, so the changes caused by writing them can be ignored.
+	fn regenerate(&mut self, changes:Vec<FileChange>) -> Result<WatchReport, Error> {
+		let rescanned:bool = changes.contains(&FileChange::Rescan);
+		let changed_files:Vec<String> = changes.into_iter().filter_map(|change| match change { FileChange::Path(path) => Some(path), FileChange::Rescan => None }).collect();
+		let rewritten_files:Vec<String> = if rescanned { self.updater.regenerate_all()? } else { self.updater.regenerate(&changed_files)? };
+		self.written_files = rewritten_files.iter().filter_map(|path| FileRef::new(path).read().ok().map(|contents| (path.clone(), content_hash(&contents)))).collect();
+		Ok(WatchReport { changed_files, rewritten_files, rescanned })
+	}
+
+	/// Whether or not a changed file still has the contents the watcher wrote to it, meaning the change was caused by the watcher itself.
+	fn is_own_write(&self, path:&str) -> bool {
+		self.written_files.iter().any(|(written_path, hash)| Path::new(written_path) == Path::new(path) && FileRef::new(path).read().is_ok_and(|contents| content_hash(&contents) == *hash))
+	}
+}
\ No newline at end of file
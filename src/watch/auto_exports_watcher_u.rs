@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ AutoExportsWatcher, ItemImportExportUpdater, WatchReport };
+	use std::{ sync::mpsc::{ self, Receiver, Sender }, time::Duration };
+
+
+	const DEBOUNCE:Duration = Duration::from_millis(50);
+	const TIMEOUT:Duration = Duration::from_secs(10);
+
+
+	/// Wait for the next report of the watcher on a separate thread, failing when no report arrives within the timeout.
+	fn next_report(mut watcher:AutoExportsWatcher) -> (AutoExportsWatcher, WatchReport) {
+		let (sender, receiver):(Sender<(AutoExportsWatcher, WatchReport)>, Receiver<(AutoExportsWatcher, WatchReport)>) = mpsc::channel();
+		std::thread::spawn(move || {
+			let report:WatchReport = watcher.next_report().unwrap();
+			let _ = sender.send((watcher, report));
+		});
+		receiver.recv_timeout(TIMEOUT).expect("no report within timeout")
+	}
+
+	/// Replace a file in a single step, so the watcher sees exactly one change regardless of the debounce time.
+	fn replace_file(path:&str, contents:&str) {
+		std::fs::write(format!("{path}.tmp"), contents).unwrap();
+		std::fs::rename(format!("{path}.tmp"), path).unwrap();
+	}
+
+
+	#[test]
+	fn test_changes_regenerate_affected_files() {
+		const CRATE_DIR:&str = "target/crate_manager/test_auto_exports_watcher_crate";
+		let _ = std::fs::remove_dir_all(CRATE_DIR);
+		std::fs::create_dir_all(CRATE_DIR).unwrap();
+		std::fs::write(format!("{CRATE_DIR}/lib.rs"), "// auto-exports\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/a.rs"), "pub fn a() {}\n").unwrap();
+		std::fs::write(format!("{CRATE_DIR}/a_test.rs"), "pub fn tested() {}\n").unwrap();
+
+		// The watcher generates with the configuration of the given updater.
+		let updater:ItemImportExportUpdater = ItemImportExportUpdater::new(&format!("{CRATE_DIR}/lib.rs")).with_test_file_suffix("_test");
+		let watcher:AutoExportsWatcher = AutoExportsWatcher::new(updater).unwrap().with_debounce(DEBOUNCE);
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\n#[cfg(test)]\nmod a_test;\nmod a;\n\npub use a::*; // a\n");
+
+		// Adding a file invalidates the module containing it, which is generated again.
+		replace_file(&format!("{CRATE_DIR}/b.rs"), "pub fn b() {}\n");
+		let (watcher, report) = next_report(watcher);
+		assert!(!report.rescanned);
+		assert!(report.changed_files.len() == 1 && report.changed_files[0].ends_with("/b.rs"));
+		assert!(report.rewritten_files.len() == 1 && report.rewritten_files[0].ends_with("/lib.rs"));
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\n#[cfg(test)]\nmod a_test;\nmod a;\nmod b;\n\npub use a::*; // a\npub use b::*; // b\n");
+
+		// The watcher's own write to lib.rs does not trigger another generation.
+		replace_file(&format!("{CRATE_DIR}/a.rs"), "pub fn a() {}\npub fn c() {}\n");
+		let (_, report) = next_report(watcher);
+		assert!(report.changed_files.len() == 1 && report.changed_files[0].ends_with("/a.rs"));
+		assert_eq!(std::fs::read_to_string(format!("{CRATE_DIR}/lib.rs")).unwrap(), "// auto-exports\n#[cfg(test)]\nmod a_test;\nmod a;\nmod b;\n\npub use a::*; // a, c\npub use b::*; // b\n");
+		std::fs::remove_dir_all(CRATE_DIR).unwrap();
+	}
+}
\ No newline at end of file
@@ -0,0 +1,282 @@
+use std::{ path::{ Path, PathBuf }, sync::mpsc::{ self, Receiver, RecvTimeoutError, Sender }, thread::{ self, JoinHandle }, time::Duration };
+use crate::Error;
+
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileChange {
+
+	/// A file or directory was created, modified or removed.
+	Path(String),
+
+	/// Events were lost, so any file in the watched directory may have changed.
+	Rescan
+}
+
+
+
+pub struct FileWatcher {
+	dir:String,
+	receiver:Receiver<FileChange>,
+	stop_signal:Option<StopSignal>,
+	thread:Option<JoinHandle<()>>
+}
+impl FileWatcher {
+
+	/// Create a new file watcher that recursively watches the given directory. Watching stops when the watcher is dropped.
+	pub fn new(dir:&str) -> Result<FileWatcher, Error> {
+		let (sender, receiver) = mpsc::channel::<FileChange>();
+		let (thread, stop_signal) = watch_dir(PathBuf::from(dir), sender).map_err(|error| Error::io(dir, error))?;
+		Ok(FileWatcher {
+			dir: dir.to_string(),
+			receiver,
+			stop_signal: Some(stop_signal),
+			thread: Some(thread)
+		})
+	}
+
+	/// Wait for the next single change, or return none if no change occurred within the timeout.
+	pub fn next_change(&self, timeout:Duration) -> Result<Option<FileChange>, Error> {
+		match self.receiver.recv_timeout(timeout) {
+			Ok(change) => Ok(Some(change)),
+			Err(RecvTimeoutError::Timeout) => Ok(None),
+			Err(RecvTimeoutError::Disconnected) => Err(Error::io(&self.dir, "file watcher stopped"))
+		}
+	}
+
+	/// Wait until one or more files change. After the first change, keeps collecting changes until no new change occurred for the duration of the debounce time. Returns the changes without duplicates.
+	pub fn wait_for_changes(&self, debounce:Duration) -> Result<Vec<FileChange>, Error> {
+		debounce_changes(&self.receiver, debounce).ok_or_else(|| Error::io(&self.dir, "file watcher stopped"))
+	}
+}
+impl Drop for FileWatcher {
+	fn drop(&mut self) {
+
+		// Releasing the stop signal wakes the watcher thread, which then exits and closes its descriptors.
+		drop(self.stop_signal.take());
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+
+
+/// Wait for a first change on the receiver, then keep collecting changes until no new change arrives for the duration of the debounce time. Returns the changes without duplicates, or none if the sender stopped.
+pub(crate) fn debounce_changes(receiver:&Receiver<FileChange>, debounce:Duration) -> Option<Vec<FileChange>> {
+	let mut changes:Vec<FileChange> = vec![receiver.recv().ok()?];
+	loop {
+		match receiver.recv_timeout(debounce) {
+			Ok(change) => {
+				if !changes.contains(&change) {
+					changes.push(change);
+				}
+			},
+			Err(RecvTimeoutError::Timeout) => return Some(changes),
+			Err(RecvTimeoutError::Disconnected) => return None
+		}
+	}
+}
+
+/// Whether or not a changed path is relevant for the watcher.
+fn is_relevant_change(path:&Path, is_dir:bool) -> bool {
+	is_dir || path.extension().is_some_and(|extension| extension == "rs")
+}
+
+/// Recursively list all directories in a directory, including the directory itself.
+fn list_dirs_recursive(dir:&Path) -> Vec<PathBuf> {
+	let mut dirs:Vec<PathBuf> = vec![dir.to_path_buf()];
+	if let Ok(entries) = std::fs::read_dir(dir) {
+		for entry in entries.flatten() {
+			if entry.path().is_dir() {
+				dirs.extend(list_dirs_recursive(&entry.path()));
+			}
+		}
+	}
+	dirs
+}
+
+
+
+/* LINUX IMPLEMENTATION */
+
+#[cfg(target_os = "linux")]
+type StopSignal = std::os::fd::OwnedFd;
+#[cfg(target_os = "linux")]
+const IN_CLOEXEC:i32 = 0o2000000;
+#[cfg(target_os = "linux")]
+const IN_WATCH_MASK:u32 = 0x00000002 | 0x00000008 | 0x00000040 | 0x00000080 | 0x00000100 | 0x00000200; // IN_MODIFY, IN_CLOSE_WRITE, IN_MOVED_FROM, IN_MOVED_TO, IN_CREATE, IN_DELETE
+#[cfg(target_os = "linux")]
+const IN_CREATED_MASK:u32 = 0x00000100 | 0x00000080; // IN_CREATE, IN_MOVED_TO
+#[cfg(target_os = "linux")]
+const IN_Q_OVERFLOW:u32 = 0x00004000;
+#[cfg(target_os = "linux")]
+const IN_IGNORED:u32 = 0x00008000;
+#[cfg(target_os = "linux")]
+const IN_ISDIR:u32 = 0x40000000;
+#[cfg(target_os = "linux")]
+const IN_EVENT_HEADER_SIZE:usize = 16;
+#[cfg(target_os = "linux")]
+const POLLIN:i16 = 0x0001;
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+struct PollFd {
+	fd:i32,
+	events:i16,
+	revents:i16
+}
+
+#[cfg(target_os = "linux")]
+extern "C" {
+	fn inotify_init1(flags:i32) -> i32;
+	fn inotify_add_watch(fd:i32, pathname:*const std::os::raw::c_char, mask:u32) -> i32;
+	fn pipe2(fds:*mut i32, flags:i32) -> i32;
+	fn poll(fds:*mut PollFd, count:std::os::raw::c_ulong, timeout:i32) -> i32;
+}
+
+/// Start watching a directory using inotify. Sends all changed paths to the sender, and a rescan when the event queue overflowed or a watch was lost. The thread stops when the returned stop signal is dropped.
+#[cfg(target_os = "linux")]
+fn watch_dir(dir:PathBuf, sender:Sender<FileChange>) -> Result<(JoinHandle<()>, StopSignal), std::io::Error> {
+	use std::{ collections::HashMap, ffi::CString, fs::File, io::{ ErrorKind, Read }, os::{ fd::{ AsRawFd, FromRawFd, OwnedFd }, unix::ffi::OsStrExt } };
+
+	// Create inotify instance and stop pipe. Descriptors are owned right away, so they are closed on every error path.
+	let fd:i32 = unsafe { inotify_init1(IN_CLOEXEC) };
+	if fd < 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+	let inotify_fd:OwnedFd = unsafe { OwnedFd::from_raw_fd(fd) };
+	let mut pipe_fds:[i32; 2] = [-1, -1];
+	if unsafe { pipe2(pipe_fds.as_mut_ptr(), IN_CLOEXEC) } < 0 {
+		return Err(std::io::Error::last_os_error());
+	}
+	let (stop_receiver, stop_signal) = unsafe { (OwnedFd::from_raw_fd(pipe_fds[0]), OwnedFd::from_raw_fd(pipe_fds[1])) };
+
+	// Watch all directories.
+	let fd:i32 = inotify_fd.as_raw_fd();
+	let add_watch = move |dir:&Path| -> Option<i32> {
+		let path:CString = CString::new(dir.as_os_str().as_bytes()).ok()?;
+		let watch_descriptor:i32 = unsafe { inotify_add_watch(fd, path.as_ptr(), IN_WATCH_MASK) };
+		if watch_descriptor < 0 { None } else { Some(watch_descriptor) }
+	};
+	let mut watched_dirs:HashMap<i32, PathBuf> = HashMap::new();
+	let watch_recursive = move |dir:&Path, watched_dirs:&mut HashMap<i32, PathBuf>| {
+		for dir in list_dirs_recursive(dir) {
+			if let Some(watch_descriptor) = add_watch(&dir) {
+				watched_dirs.insert(watch_descriptor, dir);
+			}
+		}
+	};
+	for dir in list_dirs_recursive(&dir) {
+		match add_watch(&dir) {
+			Some(watch_descriptor) => { watched_dirs.insert(watch_descriptor, dir); },
+			None => return Err(std::io::Error::last_os_error())
+		}
+	}
+
+	// Read events in a separate thread, until the stop signal is dropped.
+	let mut inotify_file:File = File::from(inotify_fd);
+	let thread:JoinHandle<()> = thread::spawn(move || {
+		let mut buffer:Vec<u8> = vec![0; 64 * 1024];
+		loop {
+			let mut poll_fds:[PollFd; 2] = [PollFd { fd, events: POLLIN, revents: 0 }, PollFd { fd: stop_receiver.as_raw_fd(), events: POLLIN, revents: 0 }];
+			if unsafe { poll(poll_fds.as_mut_ptr(), 2, -1) } < 0 {
+				if std::io::Error::last_os_error().kind() == ErrorKind::Interrupted {
+					continue;
+				}
+				return;
+			}
+			if poll_fds[1].revents != 0 {
+				return;
+			}
+			let length:usize = match inotify_file.read(&mut buffer) {
+				Ok(length) => length,
+				Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+				Err(_) => return
+			};
+			let mut cursor:usize = 0;
+			while cursor + IN_EVENT_HEADER_SIZE <= length {
+				let read_u32 = |offset:usize| u32::from_ne_bytes([buffer[cursor + offset], buffer[cursor + offset + 1], buffer[cursor + offset + 2], buffer[cursor + offset + 3]]);
+				let watch_descriptor:i32 = read_u32(0) as i32;
+				let mask:u32 = read_u32(4);
+				let name_length:usize = read_u32(12) as usize;
+				let name_bytes:&[u8] = &buffer[cursor + IN_EVENT_HEADER_SIZE..(cursor + IN_EVENT_HEADER_SIZE + name_length).min(length)];
+				let name:String = String::from_utf8_lossy(name_bytes).trim_end_matches('\0').to_string();
+				cursor += IN_EVENT_HEADER_SIZE + name_length;
+
+				// Events were dropped by the kernel, so everything may have changed.
+				let mut change:Option<FileChange> = None;
+				if mask & IN_Q_OVERFLOW != 0 {
+					change = Some(FileChange::Rescan);
+				}
+
+				// A removed watch is only expected when its directory is removed, which the parent directory reports. If the directory still exists, changes in it may have been missed.
+				else if mask & IN_IGNORED != 0 {
+					if let Some(dir) = watched_dirs.remove(&watch_descriptor).filter(|dir| dir.is_dir()) {
+						watch_recursive(&dir, &mut watched_dirs);
+						change = Some(FileChange::Rescan);
+					}
+				}
+
+				// Handle the change.
+				else if let Some(parent_dir) = watched_dirs.get(&watch_descriptor) {
+					let path:PathBuf = parent_dir.join(&name);
+					let is_dir:bool = mask & IN_ISDIR != 0;
+					if is_dir && mask & IN_CREATED_MASK != 0 {
+						watch_recursive(&path, &mut watched_dirs);
+					}
+					if is_relevant_change(&path, is_dir) {
+						change = Some(FileChange::Path(path.to_string_lossy().to_string()));
+					}
+				}
+				if change.is_some_and(|change| sender.send(change).is_err()) {
+					return;
+				}
+			}
+		}
+	});
+	Ok((thread, stop_signal))
+}
+
+
+
+/* FALLBACK IMPLEMENTATION */
+
+#[cfg(not(target_os = "linux"))]
+type StopSignal = Sender<()>;
+#[cfg(not(target_os = "linux"))]
+const POLL_INTERVAL:Duration = Duration::from_millis(500);
+
+/// Start watching a directory by periodically comparing modification times. Sends all changed paths to the sender. The thread stops when the returned stop signal is dropped.
+#[cfg(not(target_os = "linux"))]
+fn watch_dir(dir:PathBuf, sender:Sender<FileChange>) -> Result<(JoinHandle<()>, StopSignal), std::io::Error> {
+	use std::{ collections::HashMap, time::SystemTime };
+
+	// Take snapshots of all relevant files and their modification times.
+	let snapshot = move || -> HashMap<PathBuf, Option<SystemTime>> {
+		list_dirs_recursive(&dir).iter()
+			.filter_map(|dir| std::fs::read_dir(dir).ok())
+			.flat_map(|entries| entries.flatten())
+			.map(|entry| entry.path())
+			.filter(|path| is_relevant_change(path, path.is_dir()))
+			.map(|path| { let modified:Option<SystemTime> = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok(); (path, modified) })
+			.collect()
+	};
+
+	// Compare snapshots in a separate thread, until the stop signal is dropped.
+	let (stop_signal, stop_receiver) = mpsc::channel::<()>();
+	let mut previous:HashMap<PathBuf, Option<SystemTime>> = snapshot();
+	let thread:JoinHandle<()> = thread::spawn(move || {
+		while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(POLL_INTERVAL) {
+			let current:HashMap<PathBuf, Option<SystemTime>> = snapshot();
+			let changed_paths:Vec<&PathBuf> = current.iter().filter(|(path, modified)| previous.get(*path) != Some(modified)).map(|(path, _)| path).chain(previous.keys().filter(|path| !current.contains_key(*path))).collect();
+			for path in changed_paths {
+				if sender.send(FileChange::Path(path.to_string_lossy().to_string())).is_err() {
+					return;
+				}
+			}
+			previous = current;
+		}
+	});
+	Ok((thread, stop_signal))
+}
\ No newline at end of file
@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+	use crate::{ debounce_changes, FileChange, FileWatcher };
+	use std::{ sync::mpsc::{ self, Receiver, Sender }, time::Duration };
+
+
+	const TIMEOUT:Duration = Duration::from_secs(10);
+
+
+	#[test]
+	fn test_changes_are_reported() {
+		const TEST_DIR:&str = "target/crate_manager/test_file_watcher";
+		let _ = std::fs::remove_dir_all(TEST_DIR);
+		std::fs::create_dir_all(TEST_DIR).unwrap();
+		let watcher:FileWatcher = FileWatcher::new(TEST_DIR).unwrap();
+
+		// Files that are not Rust files are ignored. Events arrive in order, so the ignored file would be reported before the others.
+		std::fs::write(format!("{TEST_DIR}/notes.txt"), "notes\n").unwrap();
+		std::fs::write(format!("{TEST_DIR}/a.rs"), "// a\n").unwrap();
+		std::fs::write(format!("{TEST_DIR}/b.rs"), "// b\n").unwrap();
+		let expected:Vec<FileChange> = vec![FileChange::Path(format!("{TEST_DIR}/a.rs")), FileChange::Path(format!("{TEST_DIR}/b.rs"))];
+		let mut changes:Vec<FileChange> = Vec::new();
+		while !expected.iter().all(|change| changes.contains(change)) {
+			changes.push(watcher.next_change(TIMEOUT).unwrap().expect("expected change was not reported"));
+		}
+		assert!(changes.iter().all(|change| expected.contains(change)));
+		std::fs::remove_dir_all(TEST_DIR).unwrap();
+	}
+
+	#[test]
+	fn test_changes_are_debounced() {
+		const DEBOUNCE:Duration = Duration::from_millis(10);
+		let (sender, receiver):(Sender<FileChange>, Receiver<FileChange>) = mpsc::channel();
+
+		// Queued changes are collected into one set without duplicates.
+		for _ in 0..5 {
+			sender.send(FileChange::Path("a.rs".to_string())).unwrap();
+		}
+		sender.send(FileChange::Rescan).unwrap();
+		sender.send(FileChange::Path("b.rs".to_string())).unwrap();
+		sender.send(FileChange::Rescan).unwrap();
+		assert_eq!(debounce_changes(&receiver, DEBOUNCE), Some(vec![FileChange::Path("a.rs".to_string()), FileChange::Rescan, FileChange::Path("b.rs".to_string())]));
+
+		// Changes after the debounce time are reported separately.
+		sender.send(FileChange::Path("b.rs".to_string())).unwrap();
+		assert_eq!(debounce_changes(&receiver, DEBOUNCE), Some(vec![FileChange::Path("b.rs".to_string())]));
+
+		// A stopped sender is reported.
+		drop(sender);
+		assert_eq!(debounce_changes(&receiver, DEBOUNCE), None);
+	}
+
+	#[test]
+	fn test_drop_stops_watching() {
+		const TEST_DIR:&str = "target/crate_manager/test_file_watcher_drop";
+		let _ = std::fs::remove_dir_all(TEST_DIR);
+		std::fs::create_dir_all(TEST_DIR).unwrap();
+		let watcher:FileWatcher = FileWatcher::new(TEST_DIR).unwrap();
+
+		// Dropping the watcher returns without any file changing, as the watcher thread is stopped and joined.
+		let (sender, receiver):(Sender<()>, Receiver<()>) = mpsc::channel();
+		std::thread::spawn(move || {
+			drop(watcher);
+			sender.send(()).unwrap();
+		});
+		assert!(receiver.recv_timeout(TIMEOUT).is_ok());
+		std::fs::remove_dir_all(TEST_DIR).unwrap();
+	}
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+// auto-exports
+#[cfg(test)]
+mod auto_exports_watcher_u;
+mod auto_exports_watcher;
+#[cfg(test)]
+mod file_watcher_u;
+mod file_watcher;
+
+pub use auto_exports_watcher::*; // WatchReport, AutoExportsWatcher
+pub use file_watcher::*; // FileChange, FileWatcher
+pub(crate) use file_watcher::*; // debounce_changes
\ No newline at end of file